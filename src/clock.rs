@@ -0,0 +1,42 @@
+use std::time::Instant;
+
+// The simulation always advances in 60 Hz steps regardless of how often the
+// display ticks, so speeds are px/s and a 120 Hz iPad plays like a laptop.
+pub const SIM_STEP: f32 = 1.0 / 60.0;
+
+// A frame longer than this (a hitch, the app being backgrounded) is treated as
+// this long, and no single tick runs more than MAX_STEPS_PER_TICK steps.
+const MAX_FRAME_TIME: f32 = 0.25;
+const MAX_STEPS_PER_TICK: u32 = 5;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SimClock {
+    last_tick: Option<Instant>,
+    accumulator: f32,
+}
+
+impl SimClock {
+    // Returns how many fixed steps to run for the tick at `now`.
+    pub fn advance(&mut self, now: Instant) -> u32 {
+        let frame = match self.last_tick.replace(now) {
+            Some(last) => now.duration_since(last).as_secs_f32().min(MAX_FRAME_TIME),
+            None => 0.0,
+        };
+
+        self.accumulator += frame;
+        let steps = ((self.accumulator / SIM_STEP) as u32).min(MAX_STEPS_PER_TICK);
+        self.accumulator -= steps as f32 * SIM_STEP;
+
+        if steps == MAX_STEPS_PER_TICK {
+            self.accumulator = self.accumulator.min(SIM_STEP);
+        }
+
+        steps
+    }
+
+    // Forget the last tick so time spent paused is never caught up on.
+    pub fn stop(&mut self) {
+        self.last_tick = None;
+        self.accumulator = 0.0;
+    }
+}
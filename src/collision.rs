@@ -1,14 +1,15 @@
-use pelican_ui::Context;
-use pelican_game_engine::{Gameboard, Sprite};
-use std::time::Instant;
-use std::collections::HashMap;
 use std::time::Duration;
-use pelican_ui_std::Offset;
 
-use crate::player::PlayerManager;
+use crate::player::{PlayerManager, PowerUpKind, WING_ID};
 use crate::fly::fly_manager::EnemyManager;
+use crate::scoring::{Scoring, BULLET_SCORE, POPUP_DURATION};
+use crate::sim::Simulation;
+use crate::world::{Body, EntityKind};
+use crate::player::ShotKind;
 
 const EXPLOSION_DURATION: Duration = Duration::from_secs(2);
+const HIT_SPARK_DURATION: Duration = Duration::from_millis(250);
+const HIT_SPARK_SIZE: (f32, f32) = (24.0, 24.0);
 
 pub struct CollisionManager;
 
@@ -27,51 +28,50 @@ impl CollisionManager {
         x1 < x2 + w2 && x1 + w1 > x2 && y1 < y2 + h2 && y1 + h1 > y2
     }
 
-    pub fn handle_player_enemy_bullet_collisions(
-        ctx: &mut Context,
-        board: &mut Gameboard,
-        explosions: &mut HashMap<String, Instant>,
-    ) -> (bool, (f32, f32)) {
-        let active_enemy_bullets = EnemyManager::get_active_enemy_bullets(board, ctx);
-        let mut player_hit = false;
-        let mut player_hit_pos = (0.0, 0.0);
-        let mut enemy_bullets_to_remove = Vec::new();
-
-        if let Some(player_sprite) = board.2.get_mut("player") {
-            let player_pos = player_sprite.position(ctx);
-            let player_size = *player_sprite.dimensions();
-
-            for (bullet_id, bullet_pos, bullet_size) in active_enemy_bullets {
-                if Self::check_collision(bullet_pos, bullet_size, player_pos, player_size) {
-                    player_hit = true;
-                    player_hit_pos = player_pos;
-                    enemy_bullets_to_remove.push(bullet_id);
-                    break;
-                }
-            }
-        }
+    // Returns which of the player's ships was hit, and where.
+    pub fn handle_player_enemy_bullet_collisions(sim: &mut Simulation) -> Option<(String, (f32, f32))> {
+        let active_enemy_bullets = EnemyManager::get_active_enemy_bullets(&sim.world);
+        let ships = Self::player_ships(sim);
 
-        for bullet_id in enemy_bullets_to_remove {
-            Self::remove_sprite_from_board(ctx, board, &bullet_id);
-        }
+        let hit = active_enemy_bullets.into_iter().find_map(|(bullet_id, bullet_pos, bullet_size)| {
+            ships.iter()
+                .find(|(_, ship_pos, ship_size)| Self::check_collision(bullet_pos, bullet_size, *ship_pos, *ship_size))
+                .map(|(ship_id, ship_pos, _)| (bullet_id, ship_id.clone(), *ship_pos))
+        });
+
+        let (bullet_id, ship_id, ship_pos) = hit?;
+        sim.remove_body(&bullet_id);
+        Some((ship_id, ship_pos))
+    }
 
-        (player_hit, player_hit_pos)
+    // Returns the enemy whose tractor beam the player flew into.
+    pub fn handle_player_beam_collisions(sim: &Simulation) -> Option<String> {
+        let player = sim.world.get("player")?;
+        sim.world.of_kind(|kind| *kind == EntityKind::TractorBeam)
+            .find(|(_, beam)| Self::check_collision(beam.position, beam.size, player.position, player.size))
+            .and_then(|(_, beam)| beam.owner.clone())
     }
 
-    pub fn handle_bullet_bullet_collisions(
-        ctx: &mut Context,
-        board: &mut Gameboard,
-        explosions: &mut HashMap<String, Instant>,
-    ) {
-        let active_bullets = PlayerManager::get_active_bullets(board, ctx);
-        let active_enemy_bullets = EnemyManager::get_active_enemy_bullets(board, ctx);
+    // The player's ship, and the second one of a dual fighter.
+    fn player_ships(sim: &Simulation) -> Vec<(String, (f32, f32), (f32, f32))> {
+        ["player", WING_ID].iter()
+            .filter_map(|id| sim.world.get(id).map(|body| (id.to_string(), body.position, body.size)))
+            .collect()
+    }
+
+    // Returns the points for the enemy bullets shot down.
+    pub fn handle_bullet_bullet_collisions(sim: &mut Simulation) -> u32 {
+        let active_bullets = PlayerManager::get_active_bullets(&sim.world);
+        let active_enemy_bullets = EnemyManager::get_active_enemy_bullets(&sim.world);
         let mut bullet_bullet_collisions = Vec::new();
+        let mut points = 0;
 
-        for (player_bullet_id, player_pos, player_size) in &active_bullets {
+        for (player_bullet_id, kind, player_pos, player_size) in &active_bullets {
             for (enemy_bullet_id, enemy_pos, enemy_size) in &active_enemy_bullets {
                 if Self::check_collision(*player_pos, *player_size, *enemy_pos, *enemy_size) {
                     bullet_bullet_collisions.push((
                         player_bullet_id.clone(),
+                        *kind,
                         enemy_bullet_id.clone(),
                         (
                             (player_pos.0 + enemy_pos.0) / 2.0,
@@ -82,102 +82,148 @@ impl CollisionManager {
             }
         }
 
-        for (player_bullet_id, enemy_bullet_id, explosion_pos) in bullet_bullet_collisions {
-            Self::remove_sprite_from_board(ctx, board, &player_bullet_id);
-            Self::remove_sprite_from_board(ctx, board, &enemy_bullet_id);
-            Self::spawn_explosion(ctx, board, explosion_pos, explosions);
-
-            println!(
-                "Bullet–bullet collision: {} vs {} at {:?}",
-                player_bullet_id, enemy_bullet_id, explosion_pos
-            );
+        for (player_bullet_id, kind, enemy_bullet_id, explosion_pos) in bullet_bullet_collisions {
+            if kind != EntityKind::PlayerBullet(ShotKind::Piercing) {
+                sim.remove_body(&player_bullet_id);
+            }
+            sim.combo.mark_landed(&player_bullet_id);
+            if sim.world.contains(&enemy_bullet_id) {
+                points += BULLET_SCORE;
+                Scoring::spawn_popup(&mut sim.world, explosion_pos, BULLET_SCORE);
+            }
+            sim.remove_body(&enemy_bullet_id);
+            Self::spawn_explosion(sim, explosion_pos);
         }
+
+        points
     }
 
-    pub fn handle_player_bullet_enemy_collisions(
-        ctx: &mut Context,
-        board: &mut Gameboard,
-        explosions: &mut HashMap<String, Instant>,
-    ) -> u32 {
-        let active_bullets = PlayerManager::get_active_bullets(board, ctx);
+    // Returns where enemies were destroyed and the points they were worth.
+    pub fn handle_player_bullet_enemy_collisions(sim: &mut Simulation) -> (Vec<(f32, f32)>, u32) {
+        let active_bullets = PlayerManager::get_active_bullets(&sim.world);
         let mut sprites_to_remove = Vec::new();
         let mut explosions_to_spawn = Vec::new();
-        let mut collisions_count = 0;
-
-        for (bullet_id, bullet_pos, bullet_size) in active_bullets {
-            for (enemy_id, enemy_sprite) in board.2.iter_mut() {
-                if EnemyManager::is_enemy(enemy_id) {
-                    let enemy_pos = enemy_sprite.position(ctx);
-                    let enemy_size = *enemy_sprite.dimensions();
-
-                    if Self::check_collision(bullet_pos, bullet_size, enemy_pos, enemy_size) {
-                        explosions_to_spawn.push(enemy_pos);
-                        sprites_to_remove.push(bullet_id.clone());
-                        sprites_to_remove.push(enemy_id.clone());
-                        collisions_count += 1;
-                        break;
+        let mut sparks_to_spawn = Vec::new();
+        let mut popups_to_spawn = Vec::new();
+        let mut kills = Vec::new();
+        let time = sim.enemy_time;
+        let mut points = 0;
+
+        for (bullet_id, kind, bullet_pos, bullet_size) in active_bullets {
+            let piercing = kind == EntityKind::PlayerBullet(ShotKind::Piercing);
+
+            for (enemy_id, enemy) in sim.world.bodies.iter_mut() {
+                if enemy.kind.is_enemy() && enemy.hp > 0 {
+                    if Self::check_collision(bullet_pos, bullet_size, enemy.position, enemy.size) {
+                        let EntityKind::Enemy(type_id) = enemy.kind else { continue };
+                        if piercing && !sim.pierced.entry(bullet_id.clone()).or_default().insert(enemy_id.clone()) {
+                            continue;
+                        }
+                        let Some(enemy_type) = sim.enemy_types.get(type_id) else { continue };
+
+                        sim.combo.mark_landed(&bullet_id);
+                        enemy.hp -= 1;
+                        if enemy.hp == 0 {
+                            let behaviour = EnemyManager::kill_behaviour_of(&sim.enemies, enemy_id);
+                            let score = Scoring::enemy_kill(enemy_type, behaviour, &sim.enemies.modifiers);
+                            let score = sim.combo.kill(score, sim.time);
+                            let center = (enemy.position.0 + enemy.size.0 / 2.0, enemy.position.1 + enemy.size.1 / 2.0);
+                            points += score;
+                            popups_to_spawn.push((center, score));
+                            explosions_to_spawn.push(enemy.position);
+                            EnemyManager::kill_enemy(&mut sim.enemies, enemy_id, time);
+                            kills.push(center);
+                        } else {
+                            // Survived: no points yet, just a spark where the
+                            // bullet struck and the damaged look.
+                            if let Some(damaged) = &enemy_type.damaged_sprite {
+                                enemy.image = damaged.clone();
+                            }
+                            sparks_to_spawn.push((
+                                bullet_pos.0 + (bullet_size.0 - HIT_SPARK_SIZE.0) / 2.0,
+                                bullet_pos.1 - HIT_SPARK_SIZE.1 / 2.0,
+                            ));
+                            EnemyManager::hit_enemy(&mut sim.enemies, enemy_id, time);
+                        }
+
+                        if !piercing {
+                            sprites_to_remove.push(bullet_id.clone());
+                            break;
+                        }
                     }
                 }
             }
         }
 
         for pos in explosions_to_spawn {
-            Self::spawn_explosion(ctx, board, pos, explosions);
+            Self::spawn_explosion(sim, pos);
+        }
+
+        for pos in sparks_to_spawn {
+            Self::spawn_hit_spark(sim, pos);
+        }
+
+        for (center, score) in popups_to_spawn {
+            Scoring::spawn_popup(&mut sim.world, center, score);
         }
 
         for sprite_id in sprites_to_remove {
-            Self::remove_sprite_from_board(ctx, board, &sprite_id);
+            sim.remove_body(&sprite_id);
         }
 
-        collisions_count
+        (kills, points)
     }
 
-    pub fn spawn_explosion(
-        ctx: &mut Context,
-        board: &mut Gameboard,
-        pos: (f32, f32),
-        explosions: &mut HashMap<String, Instant>,
-    ) {
-        let id = format!("explosion_{}", uuid::Uuid::new_v4());
-        let sprite = Sprite::new(
-            ctx,
-            &id,
-            "explosion",
-            (50.0, 50.0),
-            (Offset::Static(pos.0), Offset::Static(pos.1)),
-        );
-        board.insert_sprite(ctx, sprite);
-        explosions.insert(id, Instant::now());
-        println!("Spawned explosion at {:?}", pos);
+    // Returns the kinds of power-up the player caught.
+    pub fn handle_player_power_up_collisions(sim: &mut Simulation) -> Vec<PowerUpKind> {
+        let ships = Self::player_ships(sim);
+
+        let caught: Vec<(String, PowerUpKind)> = sim.world.of_kind(EntityKind::is_power_up)
+            .filter(|(_, body)| {
+                ships.iter().any(|(_, ship_pos, ship_size)| Self::check_collision(body.position, body.size, *ship_pos, *ship_size))
+            })
+            .filter_map(|(id, body)| match body.kind {
+                EntityKind::PowerUp(kind) => Some((id.clone(), kind)),
+                _ => None,
+            })
+            .collect();
+
+        caught.into_iter()
+            .map(|(id, kind)| {
+                sim.remove_body(&id);
+                kind
+            })
+            .collect()
     }
 
-    pub fn update_explosions(
-        ctx: &mut Context,
-        board: &mut Gameboard,
-        explosions: &mut HashMap<String, Instant>,
-    ) {
-        let now = Instant::now();
-        let mut expired_explosions = Vec::new();
-
-        for (id, time) in explosions.iter() {
-            if now.duration_since(*time) >= EXPLOSION_DURATION {
-                expired_explosions.push(id.clone());
-            }
-        }
+    pub fn spawn_explosion(sim: &mut Simulation, pos: (f32, f32)) {
+        let id = sim.world.next_id("explosion_");
+        sim.world.insert(&id, Body::new(EntityKind::Explosion, "explosion", pos, (50.0, 50.0)));
+    }
 
-        for id in expired_explosions {
-            Self::remove_sprite_from_board(ctx, board, &id);
-            explosions.remove(&id);
-        }
+    pub fn spawn_hit_spark(sim: &mut Simulation, pos: (f32, f32)) {
+        let id = sim.world.next_id("hit_spark_");
+        sim.world.insert(&id, Body::new(EntityKind::HitSpark, "hit_spark", pos, HIT_SPARK_SIZE));
     }
 
-    fn remove_sprite_from_board(ctx: &mut Context, board: &mut Gameboard, sprite_id: &str) {
-        if board.2.remove(sprite_id).is_some() {
-            board.0.0.remove(sprite_id);
+    // Clears explosions, hit sparks and score popups once they have been
+    // shown for long enough.
+    pub fn update_explosions(sim: &mut Simulation) {
+        let expired_explosions: Vec<String> = sim.world.bodies.iter()
+            .filter(|(_, body)| {
+                let duration = match body.kind {
+                    EntityKind::Explosion => EXPLOSION_DURATION,
+                    EntityKind::HitSpark => HIT_SPARK_DURATION,
+                    EntityKind::ScorePopup(_) => POPUP_DURATION,
+                    _ => return false,
+                };
+                sim.time - body.spawned_at >= duration.as_secs_f32()
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
 
-            if EnemyManager::is_enemy(sprite_id) {
-                EnemyManager::remove_enemy_from_base_positions(sprite_id);
-            }
+        for id in expired_explosions {
+            sim.remove_body(&id);
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,149 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_boss::EnemyBoss;
+use crate::fly::fly_state::{Capture, Dive, EnemyBehaviour, EnemyGlobalState};
+use crate::fly::fly_types::EnemyTypes;
+use crate::world::{Body, EntityKind, World};
+
+pub const TRACTOR_BEAM_ID: &str = "tractor_beam";
+pub const CAPTURED_SHIP_ID: &str = "captured_ship";
+
+const BEAM_INTERVAL: f32 = 12.0;
+const BEAM_WIDTH: f32 = 70.0;
+
+pub struct EnemyBeams;
+
+impl EnemyBeams {
+    // Sends out a beam diver every so often while `launch` is set. There is
+    // only ever one beam out, and none while a ship is already captured.
+    pub fn update(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, rng: &mut StdRng, time: f32, launch: bool) {
+        if launch {
+            Self::launch_beam(globals, world, types, rng, time);
+        }
+        Self::sync_beam(globals, world);
+        Self::carry_ship(globals, world);
+    }
+
+    fn launch_beam(globals: &mut EnemyGlobalState, world: &World, types: &EnemyTypes, rng: &mut StdRng, time: f32) {
+        if globals.capture.is_some()
+            || globals.dives.values().any(|dive| dive.beam)
+            || time - globals.last_beam < BEAM_INTERVAL
+        {
+            return;
+        }
+        let Some(player) = world.get("player") else { return };
+        let target = player.position;
+        globals.last_beam = time;
+
+        let candidates: Vec<&String> = globals.base_positions.keys()
+            .filter(|id| {
+                EnemyBehaviours::behaviour_of(globals, id).is_some_and(EnemyBehaviours::can_dive)
+                    && !EnemyBoss::is_boss(globals, id)
+                    && world.get(id).is_some_and(|body| match body.kind {
+                        EntityKind::Enemy(type_id) => types.get(type_id).is_some_and(|t| t.tractor_beam.is_some()),
+                        _ => false,
+                    })
+            })
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        let enemy_id = candidates[rng.random_range(0..candidates.len())].clone();
+        let side = if globals.base_positions[&enemy_id].0 < world.field.0 / 2.0 { -1.0 } else { 1.0 };
+
+        EnemyBehaviours::set(globals, &enemy_id, EnemyBehaviour::Diving, time);
+        globals.dives.insert(enemy_id, Dive {
+            target,
+            side,
+            shot: true,
+            beam: true,
+        });
+    }
+
+    // The beam hangs from whichever enemy is beaming down to the bottom of
+    // the board, and goes away as soon as it stops.
+    fn sync_beam(globals: &EnemyGlobalState, world: &mut World) {
+        let beamer = globals.dives.iter()
+            .filter(|(id, dive)| dive.beam && EnemyBehaviours::behaviour_of(globals, id) == Some(EnemyBehaviour::Beaming))
+            .find_map(|(id, _)| world.get(id).map(|body| (id.clone(), body.position, body.size)));
+
+        let Some((enemy_id, position, size)) = beamer else {
+            world.remove(TRACTOR_BEAM_ID);
+            return;
+        };
+
+        let top = position.1 + size.1;
+        let beam_position = (position.0 + (size.0 - BEAM_WIDTH) / 2.0, top);
+        let beam_size = (BEAM_WIDTH, (world.field.1 - top).max(0.0));
+        match world.get_mut(TRACTOR_BEAM_ID) {
+            Some(beam) => {
+                beam.position = beam_position;
+                beam.size = beam_size;
+                beam.owner = Some(enemy_id);
+            }
+            None => {
+                let beam = Body::new(EntityKind::TractorBeam, "tractor_beam", beam_position, beam_size).with_owner(&enemy_id);
+                world.insert(TRACTOR_BEAM_ID, beam);
+            }
+        }
+    }
+
+    // A captured ship rides just above its captor. Once the captor is gone
+    // without being shot down the ship goes with it.
+    fn carry_ship(globals: &EnemyGlobalState, world: &mut World) {
+        let captor = globals.capture.as_ref()
+            .filter(|capture| capture.holding)
+            .and_then(|capture| world.get(&capture.captor))
+            .map(|body| (body.position, body.size));
+
+        match captor {
+            Some((position, size)) => {
+                let Some(ship) = world.get_mut(CAPTURED_SHIP_ID) else { return };
+                ship.position = (position.0 + (size.0 - ship.size.0) / 2.0, position.1 - ship.size.1);
+            }
+            None if !globals.ship_freed => {
+                world.remove(CAPTURED_SHIP_ID);
+            }
+            None => {}
+        }
+    }
+
+    // The player has been caught in `captor`'s beam. The beam is held for
+    // its full time again from here so the pull can finish.
+    pub fn start_capture(globals: &mut EnemyGlobalState, captor: &str, time: f32) {
+        if let Some(agent) = globals.agents.get_mut(captor).filter(|agent| agent.behaviour == EnemyBehaviour::Beaming) {
+            agent.since = time;
+        }
+        globals.capture = Some(Capture { captor: captor.to_string(), holding: false });
+    }
+
+    // Where the captor is while it is still pulling the player in.
+    pub fn captor_position(globals: &EnemyGlobalState, world: &World) -> Option<((f32, f32), (f32, f32))> {
+        let capture = globals.capture.as_ref().filter(|capture| !capture.holding)?;
+        world.get(&capture.captor).map(|body| (body.position, body.size))
+    }
+
+    // The pull is done and the player's ship now belongs to the captor.
+    pub fn hold_ship(globals: &mut EnemyGlobalState, world: &mut World, size: (f32, f32)) {
+        let Some(capture) = globals.capture.as_mut() else { return };
+        let Some(position) = world.get(&capture.captor).map(|body| body.position) else { return };
+        capture.holding = true;
+        world.insert(CAPTURED_SHIP_ID, Body::new(EntityKind::CapturedShip, "spaceship_captured", position, size));
+    }
+
+    // Shooting down the captor ends the capture. If it was carrying a ship
+    // that ship is freed for the player to pick up.
+    pub fn captor_destroyed(globals: &mut EnemyGlobalState, enemy_id: &str) {
+        let Some(capture) = globals.capture.take_if(|capture| capture.captor == enemy_id) else { return };
+        if capture.holding {
+            globals.ship_freed = true;
+        }
+    }
+
+    pub fn take_freed_ship(globals: &mut EnemyGlobalState) -> bool {
+        std::mem::take(&mut globals.ship_freed)
+    }
+}
@@ -0,0 +1,205 @@
+use crate::fly::fly_state::{EnemyAgent, EnemyBehaviour, EnemyGlobalState};
+use crate::fly::fly_types::{EnemyType, EnemyTypes};
+use crate::world::{EntityKind, World};
+
+pub struct EnemyBehaviours;
+
+impl EnemyBehaviours {
+    // How long the enemy type stays in a timed behaviour. In formation and
+    // passing last until something else happens.
+    pub fn duration(enemy_type: &EnemyType, behaviour: EnemyBehaviour) -> Option<f32> {
+        match behaviour {
+            EnemyBehaviour::Entering => Some(enemy_type.entry_duration),
+            EnemyBehaviour::InFormation => None,
+            EnemyBehaviour::Diving => Some(enemy_type.dive_duration),
+            EnemyBehaviour::Returning => Some(enemy_type.return_duration),
+            EnemyBehaviour::Hit => Some(enemy_type.hit_duration),
+            EnemyBehaviour::Dying => Some(enemy_type.dying_duration),
+            EnemyBehaviour::Passing => None,
+            EnemyBehaviour::Beaming => Some(enemy_type.tractor_beam.unwrap_or(0.0)),
+        }
+    }
+
+    // Where a timed behaviour leads once it runs out. Hit goes back to
+    // whatever was interrupted and Dying ends with the enemy removed. Beam
+    // dives go on to Beaming rather than straight back, see `update`.
+    fn next(behaviour: EnemyBehaviour) -> Option<EnemyBehaviour> {
+        match behaviour {
+            EnemyBehaviour::Entering => Some(EnemyBehaviour::InFormation),
+            EnemyBehaviour::Diving | EnemyBehaviour::Beaming => Some(EnemyBehaviour::Returning),
+            EnemyBehaviour::Returning => Some(EnemyBehaviour::InFormation),
+            EnemyBehaviour::InFormation | EnemyBehaviour::Hit | EnemyBehaviour::Dying | EnemyBehaviour::Passing => None,
+        }
+    }
+
+    pub fn can_shoot(behaviour: EnemyBehaviour) -> bool {
+        matches!(behaviour, EnemyBehaviour::InFormation | EnemyBehaviour::Diving | EnemyBehaviour::Returning)
+    }
+
+    pub fn can_dive(behaviour: EnemyBehaviour) -> bool {
+        behaviour == EnemyBehaviour::InFormation
+    }
+
+    pub fn behaviour_of(globals: &EnemyGlobalState, enemy_id: &str) -> Option<EnemyBehaviour> {
+        globals.agents.get(enemy_id).map(|agent| agent.behaviour)
+    }
+
+    // What the enemy was doing before any hit stall, for scoring a kill.
+    pub fn underlying(globals: &EnemyGlobalState, enemy_id: &str) -> Option<EnemyBehaviour> {
+        let agent = globals.agents.get(enemy_id)?;
+        match (agent.behaviour, agent.resume) {
+            (EnemyBehaviour::Hit, Some((behaviour, _))) => Some(behaviour),
+            (behaviour, _) => Some(behaviour),
+        }
+    }
+
+    // Fraction of the way through the current timed behaviour, 0 to 1.
+    pub fn progress(agent: &EnemyAgent, enemy_type: &EnemyType, time: f32) -> f32 {
+        match Self::duration(enemy_type, agent.behaviour) {
+            Some(duration) if duration > 0.0 => ((time - agent.since) / duration).clamp(0.0, 1.0),
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn set(globals: &mut EnemyGlobalState, enemy_id: &str, behaviour: EnemyBehaviour, time: f32) {
+        globals.agents.insert(enemy_id.to_string(), EnemyAgent { behaviour, since: time, resume: None });
+    }
+
+    // A hit that didn't kill. The enemy stalls for the type's hit time and
+    // then carries on from where it was.
+    pub fn hit(globals: &mut EnemyGlobalState, enemy_id: &str, time: f32) {
+        let Some(agent) = globals.agents.get_mut(enemy_id) else { return };
+        match agent.behaviour {
+            EnemyBehaviour::Dying => {}
+            EnemyBehaviour::Hit => agent.since = time,
+            behaviour => {
+                agent.resume = Some((behaviour, agent.since));
+                agent.behaviour = EnemyBehaviour::Hit;
+                agent.since = time;
+            }
+        }
+    }
+
+    pub fn kill(globals: &mut EnemyGlobalState, enemy_id: &str, time: f32) {
+        Self::set(globals, enemy_id, EnemyBehaviour::Dying, time);
+    }
+
+    // Moves every enemy whose timer has run out on to its next behaviour.
+    // Returns the enemies that have finished dying, for the caller to remove.
+    pub fn update(globals: &mut EnemyGlobalState, world: &World, types: &EnemyTypes, time: f32) -> Vec<String> {
+        let mut finished = Vec::new();
+        let mut arrived = Vec::new();
+        let mut returned = Vec::new();
+
+        for (enemy_id, agent) in globals.agents.iter_mut() {
+            let Some(body) = world.get(enemy_id) else { continue };
+            let EntityKind::Enemy(type_id) = body.kind else { continue };
+            let Some(enemy_type) = types.get(type_id) else { continue };
+
+            let Some(duration) = Self::duration(enemy_type, agent.behaviour) else { continue };
+            if time - agent.since < duration {
+                continue;
+            }
+
+            match agent.behaviour {
+                EnemyBehaviour::Dying => finished.push(enemy_id.clone()),
+                EnemyBehaviour::Hit => {
+                    // Push the interrupted timer back by the time spent stalled.
+                    let (behaviour, since) = agent.resume.take().unwrap_or((EnemyBehaviour::InFormation, time));
+                    agent.behaviour = behaviour;
+                    agent.since = since + (time - agent.since);
+                }
+                behaviour => {
+                    if behaviour == EnemyBehaviour::Entering {
+                        arrived.push(enemy_id.clone());
+                    }
+                    if behaviour == EnemyBehaviour::Returning {
+                        returned.push(enemy_id.clone());
+                    }
+                    let beam_dive = globals.dives.get(enemy_id).is_some_and(|dive| dive.beam);
+                    let next = match behaviour {
+                        EnemyBehaviour::Diving if beam_dive => Some(EnemyBehaviour::Beaming),
+                        behaviour => Self::next(behaviour),
+                    };
+                    if let Some(next) = next {
+                        agent.behaviour = next;
+                        agent.since += duration;
+                    }
+                }
+            }
+        }
+
+        for enemy_id in arrived {
+            globals.entries.remove(&enemy_id);
+        }
+        for enemy_id in returned {
+            globals.dives.remove(&enemy_id);
+        }
+
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Body;
+
+    // b2 enters in 2.5s, dives in 1.9s, returns in 1.6s, stalls 0.15s on a
+    // hit and takes 0.2s to die.
+    fn setup(behaviour: EnemyBehaviour) -> (EnemyGlobalState, World, EnemyTypes) {
+        let types = EnemyTypes::bundled();
+        let b2 = types.id_of("b2").unwrap();
+        let mut world = World::default();
+        world.insert("enemy", Body::new(EntityKind::Enemy(b2), "b2", (0.0, 0.0), (50.0, 50.0)).with_hp(2));
+
+        let mut globals = EnemyGlobalState::default();
+        EnemyBehaviours::set(&mut globals, "enemy", behaviour, 0.0);
+        (globals, world, types)
+    }
+
+    fn behaviour_at(globals: &mut EnemyGlobalState, world: &World, types: &EnemyTypes, time: f32) -> Option<EnemyBehaviour> {
+        EnemyBehaviours::update(globals, world, types, time);
+        EnemyBehaviours::behaviour_of(globals, "enemy")
+    }
+
+    #[test]
+    fn an_entering_enemy_settles_into_formation() {
+        let (mut globals, world, types) = setup(EnemyBehaviour::Entering);
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 2.4), Some(EnemyBehaviour::Entering));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 2.5), Some(EnemyBehaviour::InFormation));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 100.0), Some(EnemyBehaviour::InFormation));
+    }
+
+    #[test]
+    fn a_dive_returns_and_rejoins_the_formation() {
+        let (mut globals, world, types) = setup(EnemyBehaviour::Diving);
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 1.9), Some(EnemyBehaviour::Returning));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 3.4), Some(EnemyBehaviour::Returning));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 3.5), Some(EnemyBehaviour::InFormation));
+    }
+
+    #[test]
+    fn a_hit_stalls_and_then_carries_on_where_it_was() {
+        let (mut globals, world, types) = setup(EnemyBehaviour::Diving);
+        EnemyBehaviours::hit(&mut globals, "enemy", 1.0);
+        assert_eq!(EnemyBehaviours::underlying(&globals, "enemy"), Some(EnemyBehaviour::Diving));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 1.1), Some(EnemyBehaviour::Hit));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 1.16), Some(EnemyBehaviour::Diving));
+
+        // The dive timer is pushed back by the 0.16s spent stalled.
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 2.0), Some(EnemyBehaviour::Diving));
+        assert_eq!(behaviour_at(&mut globals, &world, &types, 2.1), Some(EnemyBehaviour::Returning));
+    }
+
+    #[test]
+    fn a_killed_enemy_is_finished_once_it_has_died() {
+        let (mut globals, world, types) = setup(EnemyBehaviour::InFormation);
+        EnemyBehaviours::kill(&mut globals, "enemy", 1.0);
+        assert!(EnemyBehaviours::update(&mut globals, &world, &types, 1.1).is_empty());
+        assert_eq!(EnemyBehaviours::update(&mut globals, &world, &types, 1.2), vec!["enemy".to_string()]);
+        assert!(!EnemyBehaviours::can_shoot(EnemyBehaviour::Dying));
+        assert!(!EnemyBehaviours::can_dive(EnemyBehaviour::Dying));
+    }
+}
@@ -0,0 +1,222 @@
+use std::f32::consts::PI;
+
+use crate::fly::fly_bullets::EnemyBullets;
+use crate::fly::fly_state::{BossState, EnemyBehaviour, EnemyGlobalState, PendingEnemy};
+use crate::fly::fly_types::EnemyTypes;
+use crate::world::{EntityKind, World};
+
+pub const BOSS_TYPE: &str = "boss";
+pub const MINION_TYPE: &str = "tiki_fly";
+const BOSS_ID: &str = "boss";
+
+// Where the boss sits, as fractions of the board, and how far it sways.
+const BOSS_SLOT_Y: f32 = 0.08;
+const SWAY_WIDTH: f32 = 0.25;
+const SWAY_SPEED: f32 = 0.8;
+
+// Minions queue up in these columns on a row under the boss.
+const MINION_COLUMNS: [f32; 4] = [0.15, 0.35, 0.6, 0.8];
+const MINION_ROW: f32 = 0.35;
+const MINIONS_PER_CALL: usize = 2;
+const MAX_MINIONS: usize = 6;
+
+// Boss bullets are a bit slower than normal ones since there are more.
+const BOSS_BULLET_SPEED: f32 = 0.7;
+
+// Each phase is a bullet pattern. The boss moves to the next one as its HP
+// drops past a third and two thirds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BossPattern {
+    // A spread of shots straight down.
+    Fan,
+    // A half ring of shots all at once.
+    Ring,
+    // A single stream that sweeps back and forth.
+    Spiral,
+}
+
+impl BossPattern {
+    fn for_phase(phase: usize) -> Self {
+        match phase {
+            0 => BossPattern::Fan,
+            1 => BossPattern::Ring,
+            _ => BossPattern::Spiral,
+        }
+    }
+
+    fn interval(&self) -> f32 {
+        match self {
+            BossPattern::Fan => 1.6,
+            BossPattern::Ring => 2.0,
+            BossPattern::Spiral => 0.15,
+        }
+    }
+
+    // Seconds between minion calls, none in the first phase.
+    fn minion_interval(&self) -> Option<f32> {
+        match self {
+            BossPattern::Fan => None,
+            BossPattern::Ring => Some(8.0),
+            BossPattern::Spiral => Some(6.0),
+        }
+    }
+
+    // Directions to fire in, as angles from straight down.
+    fn angles(&self, time: f32) -> Vec<f32> {
+        match self {
+            BossPattern::Fan => (0..5).map(|i| (i as f32 - 2.0) * PI / 12.0).collect(),
+            BossPattern::Ring => (0..10).map(|i| -PI * 0.45 + i as f32 * PI * 0.1).collect(),
+            BossPattern::Spiral => vec![(time * 2.5).sin() * PI / 3.0],
+        }
+    }
+}
+
+pub struct EnemyBoss;
+
+impl EnemyBoss {
+    // Every `boss_every`th wave, counting from one. Zero turns bosses off.
+    pub fn is_boss_wave(globals: &EnemyGlobalState, types: &EnemyTypes) -> bool {
+        globals.boss_every > 0
+            && (globals.wave_count + 1).is_multiple_of(globals.boss_every)
+            && types.id_of(BOSS_TYPE).is_some()
+    }
+
+    pub fn queue_boss(globals: &mut EnemyGlobalState, world: &World, types: &EnemyTypes, time: f32) {
+        let Some(type_id) = types.id_of(BOSS_TYPE) else { return };
+        let Some(size) = types.get(type_id).map(|boss| boss.size) else { return };
+        let (w, h) = world.field;
+
+        globals.pending.push(PendingEnemy {
+            id: BOSS_ID.to_string(),
+            type_id,
+            position: ((w - size.0) / 2.0, h * BOSS_SLOT_Y),
+            spawn_at: time,
+            entry_side: -1.0,
+        });
+        globals.boss = Some(BossState {
+            id: BOSS_ID.to_string(),
+            phase: 0,
+            last_attack: time,
+            last_minions: time,
+            minion_count: 0,
+        });
+    }
+
+    pub fn is_boss(globals: &EnemyGlobalState, enemy_id: &str) -> bool {
+        globals.boss.as_ref().is_some_and(|boss| boss.id == enemy_id)
+    }
+
+    // Current and full HP while a boss is on the board, for the HP bar.
+    pub fn health(globals: &EnemyGlobalState, world: &World, types: &EnemyTypes) -> Option<(u32, u32)> {
+        let boss = globals.boss.as_ref()?;
+        let body = world.get(&boss.id)?;
+        let EntityKind::Enemy(type_id) = body.kind else { return None };
+        Some((body.hp, types.get(type_id)?.hp))
+    }
+
+    pub fn update(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32, bullet_speed: f32) {
+        let Some((hp, max_hp)) = Self::health(globals, world, types) else { return };
+        let Some(agent) = globals.boss.as_ref().and_then(|boss| globals.agents.get(&boss.id)).cloned() else { return };
+        if matches!(agent.behaviour, EnemyBehaviour::Entering | EnemyBehaviour::Dying) {
+            return;
+        }
+
+        let phase = Self::phase_for(hp, max_hp);
+        let Some(boss) = globals.boss.as_mut() else { return };
+        if phase != boss.phase {
+            boss.phase = phase;
+            // So the new phase calls its first minions straight away.
+            boss.last_minions = f32::MIN;
+        }
+        let boss = boss.clone();
+        let pattern = BossPattern::for_phase(boss.phase);
+
+        if agent.behaviour == EnemyBehaviour::InFormation {
+            Self::sway(globals, world, &boss.id, time - agent.since);
+        }
+
+        if time - boss.last_attack >= pattern.interval() {
+            Self::attack(world, types, &boss.id, pattern, time, bullet_speed * globals.modifiers.bullet_speed);
+            if let Some(boss) = globals.boss.as_mut() {
+                boss.last_attack = time;
+            }
+        }
+
+        if pattern.minion_interval().is_some_and(|interval| time - boss.last_minions >= interval) {
+            Self::call_minions(globals, world, types, time);
+        }
+    }
+
+    fn phase_for(hp: u32, max_hp: u32) -> usize {
+        let fraction = hp as f32 / max_hp.max(1) as f32;
+        if fraction > 2.0 / 3.0 { 0 } else if fraction > 1.0 / 3.0 { 1 } else { 2 }
+    }
+
+    fn sway(globals: &EnemyGlobalState, world: &mut World, boss_id: &str, elapsed: f32) {
+        let Some(&slot) = globals.base_positions.get(boss_id) else { return };
+        let width = world.field.0;
+        let Some(body) = world.get_mut(boss_id) else { return };
+
+        let x = slot.0 + (elapsed * SWAY_SPEED).sin() * width * SWAY_WIDTH;
+        body.position.0 = x.clamp(0.0, (width - body.size.0).max(0.0));
+    }
+
+    fn attack(world: &mut World, types: &EnemyTypes, boss_id: &str, pattern: BossPattern, time: f32, bullet_speed: f32) {
+        let Some(body) = world.get(boss_id) else { return };
+        let EntityKind::Enemy(type_id) = body.kind else { return };
+        let Some(enemy_type) = types.get(type_id) else { return };
+
+        let speed = bullet_speed * BOSS_BULLET_SPEED;
+        let origin = (
+            body.position.0 + (body.size.0 - enemy_type.bullet_size.0) / 2.0,
+            body.position.1 + body.size.1 * 0.8,
+        );
+
+        for angle in pattern.angles(time) {
+            let velocity = (angle.sin() * speed, angle.cos() * speed);
+            EnemyBullets::shoot_with_velocity(world, boss_id, enemy_type, origin, velocity);
+        }
+    }
+
+    // Queues a couple of minions into free columns under the boss.
+    fn call_minions(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        let Some(type_id) = types.id_of(MINION_TYPE) else { return };
+        let Some(size) = types.get(type_id).map(|minion| minion.size) else { return };
+        let Some(boss) = globals.boss.as_mut() else { return };
+        boss.last_minions = time;
+
+        let alive = world.count_of(EntityKind::is_enemy).saturating_sub(1) + globals.pending.len();
+        let room = MAX_MINIONS.saturating_sub(alive).min(MINIONS_PER_CALL);
+        let (w, h) = world.field;
+
+        for _ in 0..room {
+            let Some(boss) = globals.boss.as_mut() else { return };
+            let column = boss.minion_count as usize % MINION_COLUMNS.len();
+            boss.minion_count += 1;
+
+            let id = world.next_id("minion_");
+            globals.pending.push(PendingEnemy {
+                id,
+                type_id,
+                position: (MINION_COLUMNS[column] * w - size.0 / 2.0, MINION_ROW * h),
+                spawn_at: time,
+                entry_side: if column < MINION_COLUMNS.len() / 2 { -1.0 } else { 1.0 },
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_switch_at_two_thirds_and_one_third_hp() {
+        assert_eq!(EnemyBoss::phase_for(30, 30), 0);
+        assert_eq!(EnemyBoss::phase_for(21, 30), 0);
+        assert_eq!(EnemyBoss::phase_for(20, 30), 1);
+        assert_eq!(EnemyBoss::phase_for(11, 30), 1);
+        assert_eq!(EnemyBoss::phase_for(10, 30), 2);
+        assert_eq!(EnemyBoss::phase_for(0, 30), 2);
+    }
+}
@@ -1,32 +1,32 @@
-use pelican_ui::Context;
-use pelican_ui_std::Offset;
-use pelican_game_engine::{Sprite, Gameboard};
-use std::time::{Duration, Instant};
 use rand::Rng;
+use rand::rngs::StdRng;
 
-const ENEMY_BULLET_SPEED: f32 = 6.0;
-const ENEMY_SHOOT_COOLDOWN: Duration = Duration::from_millis(2500); 
-const ENEMY_SHOOT_CHANCE: f32 = 0.1; 
+use crate::fly::fly_types::{EnemyType, EnemyTypes};
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_state::EnemyGlobalState;
+use crate::world::{Body, EntityKind, World};
 
 pub struct EnemyBullets;
 
 impl EnemyBullets {
     pub fn update_enemy_shooting(
-        ctx: &mut Context, 
-        board: &mut Gameboard,
-        enemy_last_shot_times: &mut std::collections::HashMap<String, Instant>
+        globals: &mut EnemyGlobalState,
+        world: &mut World,
+        types: &EnemyTypes,
+        rng: &mut StdRng,
+        time: f32,
+        bullet_speed: f32,
     ) {
-        let now = Instant::now();
+        let EnemyGlobalState { enemy_last_shot_times, agents, modifiers, .. } = globals;
         let mut enemies_to_shoot = Vec::new();
+        let fire_rate = modifiers.fire_rate.max(0.01);
+        let bullet_speed = bullet_speed * modifiers.bullet_speed;
 
-        let active_enemies: Vec<String> = board.2.keys()
-            .filter(|id| crate::fly::fly_utils::is_enemy(id))
-            .cloned()
-            .collect();
+        let active_enemies: Vec<String> = world.ids_of(EntityKind::is_enemy);
 
         for enemy_id in &active_enemies {
             if !enemy_last_shot_times.contains_key(enemy_id) {
-                enemy_last_shot_times.insert(enemy_id.clone(), Instant::now());
+                enemy_last_shot_times.insert(enemy_id.clone(), time);
             }
         }
 
@@ -38,89 +38,54 @@ impl EnemyBullets {
         }
 
         for (enemy_id, last_shot) in enemy_last_shot_times.iter_mut() {
-            if now.duration_since(*last_shot) >= ENEMY_SHOOT_COOLDOWN {
-                let mut rng = rand::thread_rng();
-                if rng.gen_range(0.0..1.0) < ENEMY_SHOOT_CHANCE {
-                    if let Some(sprite) = board.2.get_mut(enemy_id) {
-                        let pos = sprite.position(ctx);
-                        let size = *sprite.dimensions();
-                        enemies_to_shoot.push((enemy_id.clone(), pos, size));
-                        *last_shot = now;
-                    }
-                } else {
-                    *last_shot = now;
+            // The cooldown only starts once the enemy is free to fire again.
+            if agents.get(enemy_id).is_some_and(|agent| !EnemyBehaviours::can_shoot(agent.behaviour)) {
+                *last_shot = time;
+                continue;
+            }
+
+            let Some(body) = world.get(enemy_id) else { continue };
+            let EntityKind::Enemy(type_id) = body.kind else { continue };
+            let Some(enemy_type) = types.get(type_id) else { continue };
+
+            if time - *last_shot >= enemy_type.fire_cooldown / fire_rate {
+                if rng.random_range(0.0..1.0) < enemy_type.shot_chance {
+                    enemies_to_shoot.push((enemy_id.clone(), enemy_type, body.position, body.size));
                 }
+                *last_shot = time;
             }
         }
 
-        for (_enemy_id, pos, size) in enemies_to_shoot {
-            Self::enemy_shoot(ctx, board, pos, size);
+        for (enemy_id, enemy_type, pos, size) in enemies_to_shoot {
+            Self::enemy_shoot(world, &enemy_id, enemy_type, pos, size, bullet_speed);
         }
     }
 
-    fn enemy_shoot(ctx: &mut Context, board: &mut Gameboard, enemy_pos: (f32, f32), enemy_size: (f32, f32)) {
-        let bullet_size = (12.0, 12.0);
+    pub fn enemy_shoot(world: &mut World, enemy_id: &str, enemy_type: &EnemyType, enemy_pos: (f32, f32), enemy_size: (f32, f32), bullet_speed: f32) {
         let (x, y) = enemy_pos;
-        let bullet_id = format!("enemy_bullet_{}", uuid::Uuid::new_v4());
-
-        let bullet = Sprite::new(
-            ctx,
-            &bullet_id,
-            "bullet_downward",
-            bullet_size,
-            (
-                Offset::Static(x + ((enemy_size.0 - bullet_size.0) / 2.0)),
-                Offset::Static(y + enemy_size.1),
-            ),
-        );
-        board.insert_sprite(ctx, bullet);
+        let position = (x + ((enemy_size.0 - enemy_type.bullet_size.0) / 2.0), y + enemy_size.1);
+        Self::shoot_with_velocity(world, enemy_id, enemy_type, position, (0.0, bullet_speed));
     }
 
-    pub fn update_enemy_bullets(ctx: &mut Context, board: &mut Gameboard) -> Vec<String> {
-        let mut bullets_to_remove = Vec::new();
-        let (_, board_height) = board.0.size(ctx);
-
-        for (id, sprite) in board.2.iter_mut() {
-            if id.starts_with("enemy_bullet_") {
-                sprite.adjustments().1 += ENEMY_BULLET_SPEED;
-                let pos = sprite.position(ctx);
-
-                if pos.1 > board_height + 50.0 {
-                    bullets_to_remove.push(id.clone());
-                }
-            }
-        }
-
-        bullets_to_remove
+    pub fn shoot_with_velocity(world: &mut World, enemy_id: &str, enemy_type: &EnemyType, position: (f32, f32), velocity: (f32, f32)) {
+        let bullet_id = world.next_id("enemy_bullet_");
+        let bullet = Body::new(EntityKind::EnemyBullet, &enemy_type.bullet_sprite, position, enemy_type.bullet_size)
+            .with_owner(enemy_id)
+            .with_velocity(velocity);
+        world.insert(&bullet_id, bullet);
     }
 
-    pub fn get_active_enemy_bullets(
-        board: &mut Gameboard,
-        ctx: &mut Context
-    ) -> Vec<(String, (f32, f32), (f32, f32))> {
-        let mut active_bullets = Vec::new();
-
-        for (id, sprite) in board.2.iter_mut() {
-            if id.starts_with("enemy_bullet_") {
-                let pos = sprite.position(ctx);
-                let size = *sprite.dimensions();
-                active_bullets.push((id.clone(), pos, size));
-            }
-        }
-
-        active_bullets
+    pub fn get_active_enemy_bullets(world: &World) -> Vec<(String, (f32, f32), (f32, f32))> {
+        world.of_kind(|kind| *kind == EntityKind::EnemyBullet)
+            .map(|(id, body)| (id.clone(), body.position, body.size))
+            .collect()
     }
 
-    pub fn clear_all_enemy_bullets(ctx: &mut Context, board: &mut Gameboard) {
-        let bullet_ids: Vec<String> = board.2.keys()
-            .filter(|id| id.starts_with("enemy_bullet_"))
-            .cloned()
-            .collect();
+    pub fn clear_all_enemy_bullets(world: &mut World) {
+        let bullet_ids = world.ids_of(|kind| *kind == EntityKind::EnemyBullet);
 
         for bullet_id in bullet_ids {
-            if board.2.remove(&bullet_id).is_some() {
-                board.0.0.remove(&bullet_id);
-            }
+            world.remove(&bullet_id);
         }
     }
 }
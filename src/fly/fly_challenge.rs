@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_state::{ChallengeState, EnemyBehaviour, EnemyGlobalState, QueuedPass};
+use crate::fly::fly_types::EnemyTypes;
+use crate::fly::fly_utils::bezier;
+use crate::world::{Body, EntityKind, World};
+
+// Groups of enemies follow each other along the same path, one group after
+// another, like the original's challenging stages.
+const GROUP_TYPES: [&str; 5] = ["tiki_fly", "northrop", "tiki_fly", "b2", "northrop"];
+const GROUP_SIZE: usize = 8;
+const GROUP_GAP: f32 = 3.0;
+const FOLLOW_GAP: f32 = 0.22;
+const PASS_DURATION: f32 = 4.0;
+
+// Cubic paths in board fractions, starting and ending off the board. Odd
+// groups fly them mirrored.
+const PATHS: [[(f32, f32); 4]; 3] = [
+    [(-0.1, 0.1), (0.9, 0.0), (0.9, 0.8), (-0.1, 0.6)],
+    [(0.5, -0.1), (-0.1, 0.7), (1.1, 0.7), (0.5, -0.1)],
+    [(-0.1, 0.5), (0.3, -0.1), (0.7, 1.0), (1.1, 0.4)],
+];
+
+pub struct EnemyChallenge;
+
+impl EnemyChallenge {
+    // Every `challenge_every`th wave, counting from one. Zero turns them off.
+    pub fn is_challenge_wave(globals: &EnemyGlobalState) -> bool {
+        globals.challenge_every > 0 && (globals.wave_count + 1).is_multiple_of(globals.challenge_every)
+    }
+
+    pub fn queue_stage(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        let mut queue = Vec::new();
+
+        for (group, name) in GROUP_TYPES.iter().enumerate() {
+            let Some(type_id) = types.id_of(name) else { continue };
+
+            let mirror = group % 2 == 1;
+            let path = PATHS[group % PATHS.len()].map(|(x, y)| (if mirror { 1.0 - x } else { x }, y));
+
+            for i in 0..GROUP_SIZE {
+                queue.push(QueuedPass {
+                    id: world.next_id("challenge_"),
+                    type_id,
+                    path,
+                    spawn_at: time + group as f32 * GROUP_GAP + i as f32 * FOLLOW_GAP,
+                });
+            }
+        }
+
+        globals.challenge = Some(ChallengeState {
+            total: queue.len() as u32,
+            queue,
+            paths: BTreeMap::new(),
+            hits: 0,
+        });
+    }
+
+    // Whether there are still enemies waiting to fly in, so the wave isn't
+    // over just because the board is empty between groups.
+    pub fn has_queued(globals: &EnemyGlobalState) -> bool {
+        globals.challenge.as_ref().is_some_and(|challenge| !challenge.queue.is_empty())
+    }
+
+    pub fn record_hit(globals: &mut EnemyGlobalState) {
+        if let Some(challenge) = globals.challenge.as_mut() {
+            challenge.hits += 1;
+        }
+    }
+
+    // Brings in due enemies and flies the rest along their paths. Returns the
+    // enemies that made it off the far side, for the caller to remove.
+    pub fn update(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) -> Vec<String> {
+        let Some(challenge) = globals.challenge.as_mut() else { return Vec::new() };
+        let field = world.field;
+
+        let (ready, waiting): (Vec<QueuedPass>, Vec<QueuedPass>) = std::mem::take(&mut challenge.queue)
+            .into_iter()
+            .partition(|pass| pass.spawn_at <= time);
+        challenge.queue = waiting;
+
+        let mut arrived = Vec::new();
+        for pass in ready {
+            let Some(enemy_type) = types.get(pass.type_id) else { continue };
+            let start = Self::path_position(&pass.path, field, enemy_type.size, 0.0);
+            let body = Body::new(EntityKind::Enemy(pass.type_id), &enemy_type.sprite, start, enemy_type.size)
+                .with_hp(enemy_type.hp);
+            world.insert(&pass.id, body);
+            challenge.paths.insert(pass.id.clone(), pass.path);
+            arrived.push(pass.id);
+        }
+
+        let mut escaped = Vec::new();
+        for (enemy_id, path) in challenge.paths.iter() {
+            let Some(agent) = globals.agents.get(enemy_id) else { continue };
+            if agent.behaviour != EnemyBehaviour::Passing {
+                continue;
+            }
+            let Some(body) = world.get_mut(enemy_id) else { continue };
+
+            let t = (time - agent.since) / PASS_DURATION;
+            if t >= 1.0 {
+                escaped.push(enemy_id.clone());
+                continue;
+            }
+            body.position = Self::path_position(path, field, body.size, t);
+        }
+
+        for enemy_id in arrived {
+            EnemyBehaviours::set(globals, &enemy_id, EnemyBehaviour::Passing, time);
+        }
+
+        escaped
+    }
+
+    // Hits and total for the stage in play, if this wave is one.
+    pub fn result(globals: &EnemyGlobalState) -> Option<(u32, u32)> {
+        globals.challenge.as_ref().map(|challenge| (challenge.hits, challenge.total))
+    }
+
+    fn path_position(path: &[(f32, f32); 4], field: (f32, f32), size: (f32, f32), t: f32) -> (f32, f32) {
+        let (w, h) = field;
+        let [p0, p1, p2, p3] = path.map(|(x, y)| (x * w - size.0 / 2.0, y * h - size.1 / 2.0));
+        bezier(p0, p1, p2, p3, t)
+    }
+}
@@ -0,0 +1,209 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_boss::EnemyBoss;
+use crate::fly::fly_bullets::EnemyBullets;
+use crate::fly::fly_state::{Dive, EnemyBehaviour, EnemyGlobalState};
+use crate::fly::fly_types::EnemyTypes;
+use crate::fly::fly_utils::bezier;
+use crate::world::{EntityKind, World};
+
+// How far down the dive, as a fraction of it, the diver fires.
+const DIVE_SHOT_AT: f32 = 0.55;
+
+const FIRST_DIVE_INTERVAL: f32 = 4.0;
+const DIVE_INTERVAL_STEP: f32 = 0.4;
+const MIN_DIVE_INTERVAL: f32 = 1.0;
+const MAX_DIVERS: usize = 4;
+// Where a beam diver stops, as a fraction of the board height.
+const BEAM_HOVER_Y: f32 = 0.45;
+
+pub struct EnemyDives;
+
+impl EnemyDives {
+    // Later waves send divers more often and more of them at once.
+    fn dive_interval(wave: u32) -> f32 {
+        (FIRST_DIVE_INTERVAL - wave as f32 * DIVE_INTERVAL_STEP).max(MIN_DIVE_INTERVAL)
+    }
+
+    fn max_divers(wave: u32) -> usize {
+        (1 + wave as usize / 2).min(MAX_DIVERS)
+    }
+
+    // Flies every diving or returning enemy along its path. New dives are
+    // only started when `launch` is set, so turning dives off lets the ones
+    // already out finish.
+    pub fn update_dives(
+        globals: &mut EnemyGlobalState,
+        world: &mut World,
+        types: &EnemyTypes,
+        rng: &mut StdRng,
+        time: f32,
+        bullet_speed: f32,
+        launch: bool,
+    ) {
+        if launch {
+            Self::launch_dive(globals, world, rng, time);
+        }
+
+        let bullet_speed = bullet_speed * globals.modifiers.bullet_speed;
+        let field = world.field;
+        let mut shooters = Vec::new();
+
+        for (enemy_id, dive) in globals.dives.iter_mut() {
+            let Some(agent) = globals.agents.get(enemy_id) else { continue };
+            let Some(&slot) = globals.base_positions.get(enemy_id) else { continue };
+            let Some(body) = world.get_mut(enemy_id) else { continue };
+            let EntityKind::Enemy(type_id) = body.kind else { continue };
+            let Some(enemy_type) = types.get(type_id) else { continue };
+
+            let t = EnemyBehaviours::progress(agent, enemy_type, time);
+            match agent.behaviour {
+                EnemyBehaviour::Diving => {
+                    body.position = Self::dive_position(dive, slot, field, t);
+                    if !dive.shot && t >= DIVE_SHOT_AT {
+                        dive.shot = true;
+                        shooters.push((enemy_id.clone(), body.kind, body.position, body.size));
+                    }
+                }
+                EnemyBehaviour::Beaming => body.position = Self::bottom(dive, slot, field),
+                EnemyBehaviour::Returning => body.position = Self::return_position(dive, slot, field, t),
+                _ => {}
+            }
+        }
+
+        for (enemy_id, kind, position, size) in shooters {
+            let EntityKind::Enemy(type_id) = kind else { continue };
+            if let Some(enemy_type) = types.get(type_id) {
+                EnemyBullets::enemy_shoot(world, &enemy_id, enemy_type, position, size, bullet_speed);
+            }
+        }
+    }
+
+    fn launch_dive(globals: &mut EnemyGlobalState, world: &World, rng: &mut StdRng, time: f32) {
+        let wave = globals.wave_count;
+        if time - globals.last_dive < Self::dive_interval(wave) || globals.dives.len() >= Self::max_divers(wave) {
+            return;
+        }
+        globals.last_dive = time;
+
+        let candidates: Vec<&String> = globals.base_positions.keys()
+            .filter(|id| {
+                EnemyBehaviours::behaviour_of(globals, id).is_some_and(EnemyBehaviours::can_dive)
+                    && !EnemyBoss::is_boss(globals, id)
+                    && world.contains(id)
+            })
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        let enemy_id = candidates[rng.random_range(0..candidates.len())].clone();
+        let (field_w, field_h) = world.field;
+        let target = world.get("player")
+            .map(|player| (player.position.0, player.position.1))
+            .unwrap_or((field_w / 2.0, field_h * 0.8));
+        let slot_x = globals.base_positions[&enemy_id].0;
+        let side = if slot_x < field_w / 2.0 { -1.0 } else { 1.0 };
+
+        EnemyBehaviours::set(globals, &enemy_id, EnemyBehaviour::Diving, time);
+        globals.dives.insert(enemy_id, Dive {
+            target,
+            side,
+            shot: false,
+            beam: false,
+        });
+    }
+
+    // Beam dives hover higher up so the beam has room to reach the player.
+    fn bottom(dive: &Dive, slot: (f32, f32), field: (f32, f32)) -> (f32, f32) {
+        if dive.beam {
+            return (dive.target.0, (field.1 * BEAM_HOVER_Y).max(slot.1));
+        }
+        (dive.target.0, (dive.target.1 - field.1 * 0.1).max(slot.1))
+    }
+
+    // A swoop out to the side and down toward where the player was at launch.
+    fn dive_position(dive: &Dive, slot: (f32, f32), field: (f32, f32), t: f32) -> (f32, f32) {
+        let (w, h) = field;
+        let side = dive.side;
+        bezier(
+            slot,
+            (slot.0 + side * w * 0.25, slot.1 - h * 0.08),
+            (dive.target.0 + side * w * 0.15, h * 0.5),
+            Self::bottom(dive, slot, field),
+            t,
+        )
+    }
+
+    // Then a loop back up under the formation into the slot.
+    fn return_position(dive: &Dive, slot: (f32, f32), field: (f32, f32), t: f32) -> (f32, f32) {
+        let (w, h) = field;
+        let side = dive.side;
+        let bottom = Self::bottom(dive, slot, field);
+        bezier(
+            bottom,
+            (bottom.0 - side * w * 0.3, bottom.1 + h * 0.05),
+            (slot.0 - side * w * 0.2, slot.1 + h * 0.25),
+            slot,
+            t,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::fly::fly_types::EnemyTypes;
+    use crate::world::Body;
+
+    const FIELD: (f32, f32) = (800.0, 600.0);
+    const SLOT: (f32, f32) = (200.0, 100.0);
+
+    fn close(a: (f32, f32), b: (f32, f32)) -> bool {
+        (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3
+    }
+
+    #[test]
+    fn a_dive_leaves_the_slot_and_the_return_ends_back_in_it() {
+        let dive = Dive { target: (500.0, 550.0), side: -1.0, shot: false, beam: false };
+        let bottom = EnemyDives::bottom(&dive, SLOT, FIELD);
+
+        assert!(close(EnemyDives::dive_position(&dive, SLOT, FIELD, 0.0), SLOT));
+        assert!(close(EnemyDives::dive_position(&dive, SLOT, FIELD, 1.0), bottom));
+        assert!(close(EnemyDives::return_position(&dive, SLOT, FIELD, 0.0), bottom));
+        assert!(close(EnemyDives::return_position(&dive, SLOT, FIELD, 1.0), SLOT));
+        assert_eq!(bottom.0, dive.target.0);
+    }
+
+    #[test]
+    fn later_waves_dive_more_often_with_more_divers() {
+        assert!(EnemyDives::dive_interval(3) < EnemyDives::dive_interval(0));
+        assert_eq!(EnemyDives::dive_interval(100), MIN_DIVE_INTERVAL);
+        assert!(EnemyDives::max_divers(4) > EnemyDives::max_divers(0));
+        assert_eq!(EnemyDives::max_divers(100), MAX_DIVERS);
+    }
+
+    #[test]
+    fn a_launch_sends_an_enemy_in_formation_at_the_player() {
+        let types = EnemyTypes::bundled();
+        let b2 = types.id_of("b2").unwrap();
+        let mut world = World::default();
+        world.field = FIELD;
+        world.insert("enemy", Body::new(EntityKind::Enemy(b2), "b2", SLOT, (50.0, 50.0)));
+        world.insert("player", Body::new(EntityKind::Player, "spaceship", (400.0, 540.0), (50.0, 50.0)));
+
+        let mut globals = EnemyGlobalState::default();
+        globals.base_positions.insert("enemy".to_string(), SLOT);
+        EnemyBehaviours::set(&mut globals, "enemy", EnemyBehaviour::InFormation, 0.0);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        EnemyDives::launch_dive(&mut globals, &world, &mut rng, FIRST_DIVE_INTERVAL);
+
+        assert_eq!(EnemyBehaviours::behaviour_of(&globals, "enemy"), Some(EnemyBehaviour::Diving));
+        assert_eq!(globals.dives["enemy"].target, (400.0, 540.0));
+        assert_eq!(globals.dives["enemy"].side, -1.0);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_state::{EnemyBehaviour, EnemyGlobalState, Entry};
+use crate::fly::fly_types::EnemyTypes;
+use crate::fly::fly_utils::bezier;
+use crate::world::{EntityKind, World};
+
+pub const ENTRY_GROUP_SIZE: usize = 4;
+pub const ENTRY_STAGGER: f32 = 0.8;
+// Enemies shot down before they reach their slot are worth this much more.
+pub const ENTRY_BONUS: f32 = 2.0;
+
+pub struct EnemyEntries;
+
+impl EnemyEntries {
+    // Groups alternate between coming in over the top left and top right.
+    pub fn side_for_group(group: usize) -> f32 {
+        if group.is_multiple_of(2) { -1.0 } else { 1.0 }
+    }
+
+    pub fn start_position(side: f32, field: (f32, f32), size: (f32, f32)) -> (f32, f32) {
+        let (w, _) = field;
+        let x = if side < 0.0 { -size.0 } else { w };
+        (x, -size.1)
+    }
+
+    // Moves every enemy that is still entering along its path. Arrival is
+    // left to the behaviour timers.
+    pub fn update_entries(globals: &EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        let field = world.field;
+
+        for (enemy_id, entry) in globals.entries.iter() {
+            let Some(agent) = globals.agents.get(enemy_id) else { continue };
+            if agent.behaviour != EnemyBehaviour::Entering {
+                continue;
+            }
+            let Some(&slot) = globals.base_positions.get(enemy_id) else { continue };
+            let Some(body) = world.get_mut(enemy_id) else { continue };
+            let EntityKind::Enemy(type_id) = body.kind else { continue };
+            let Some(enemy_type) = types.get(type_id) else { continue };
+
+            let t = EnemyBehaviours::progress(agent, enemy_type, time);
+            body.position = Self::entry_position(entry, slot, field, t);
+        }
+    }
+
+    // Drops in from the corner, sweeps across the lower half of the board
+    // and curls back up into the slot.
+    fn entry_position(entry: &Entry, slot: (f32, f32), field: (f32, f32), t: f32) -> (f32, f32) {
+        let (w, h) = field;
+        let side = entry.side;
+        bezier(
+            entry.from,
+            (w * 0.5 + side * w * 0.1, h * 0.6),
+            (slot.0 - side * w * 0.25, slot.1 + h * 0.3),
+            slot,
+            t,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIELD: (f32, f32) = (800.0, 600.0);
+    const SIZE: (f32, f32) = (50.0, 50.0);
+
+    #[test]
+    fn groups_take_turns_coming_in_from_each_corner() {
+        assert_eq!(EnemyEntries::side_for_group(0), -1.0);
+        assert_eq!(EnemyEntries::side_for_group(1), 1.0);
+        assert_eq!(EnemyEntries::side_for_group(2), -1.0);
+
+        assert_eq!(EnemyEntries::start_position(-1.0, FIELD, SIZE), (-SIZE.0, -SIZE.1));
+        assert_eq!(EnemyEntries::start_position(1.0, FIELD, SIZE), (FIELD.0, -SIZE.1));
+    }
+
+    #[test]
+    fn the_entry_path_runs_from_off_screen_into_the_slot() {
+        let slot = (300.0, 120.0);
+        for side in [-1.0, 1.0] {
+            let entry = Entry { from: EnemyEntries::start_position(side, FIELD, SIZE), side };
+            assert_eq!(EnemyEntries::entry_position(&entry, slot, FIELD, 0.0), entry.from);
+
+            let end = EnemyEntries::entry_position(&entry, slot, FIELD, 1.0);
+            assert!((end.0 - slot.0).abs() < 1e-3 && (end.1 - slot.1).abs() < 1e-3);
+
+            // It sweeps through the lower half of the board on the way.
+            let middle = EnemyEntries::entry_position(&entry, slot, FIELD, 0.5);
+            assert!(middle.1 > slot.1);
+        }
+    }
+}
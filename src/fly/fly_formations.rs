@@ -0,0 +1,260 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use std::f32::consts::PI;
+
+use crate::fly::fly_waves::{WaveDef, WaveEnemy, WaveModifiers};
+
+// Enemies are kept inside this part of the board, in board-relative units,
+// so big formations squash rather than fall off the edge or onto the player.
+const FIELD_X: (f32, f32) = (0.02, 0.9);
+const FIELD_Y: (f32, f32) = (0.03, 0.55);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormationShape {
+    Grid,
+    V,
+    Diamond,
+    Circle,
+    SineRow,
+    Spiral,
+}
+
+impl FormationShape {
+    pub const ALL: [FormationShape; 6] = [
+        FormationShape::Grid,
+        FormationShape::V,
+        FormationShape::Diamond,
+        FormationShape::Circle,
+        FormationShape::SineRow,
+        FormationShape::Spiral,
+    ];
+}
+
+// `spacing` is the gap between neighbours as a fraction of the board width,
+// `center` is board-relative and `mix` weights the enemy types by name.
+#[derive(Debug, Clone)]
+pub struct FormationParams {
+    pub shape: FormationShape,
+    pub count: usize,
+    pub spacing: f32,
+    pub center: (f32, f32),
+    pub mix: Vec<(String, u32)>,
+}
+
+// How a wave set keeps going once its fixed waves run out: every further
+// wave is a random shape with `growth` more enemies than the last, up to
+// `max_count` or the player's own limit if that is lower.
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeneratedWaves {
+    pub start_count: usize,
+    #[serde(default)]
+    pub growth: usize,
+    pub max_count: usize,
+    pub spacing: f32,
+    #[serde(default = "default_shapes")]
+    pub shapes: Vec<FormationShape>,
+    pub mix: Vec<(String, u32)>,
+    #[serde(default)]
+    pub modifiers: WaveModifiers,
+}
+
+fn default_shapes() -> Vec<FormationShape> {
+    FormationShape::ALL.to_vec()
+}
+
+impl GeneratedWaves {
+    // `index` counts generated waves only, starting at 0.
+    pub fn wave(&self, index: usize, limit: Option<usize>, aspect: f32, rng: &mut StdRng) -> WaveDef {
+        let shapes = if self.shapes.is_empty() { &FormationShape::ALL[..] } else { &self.shapes[..] };
+        let shape = shapes[rng.random_range(0..shapes.len())];
+        let max = limit.map_or(self.max_count, |limit| limit.min(self.max_count));
+        let count = (self.start_count + index * self.growth).clamp(1, max.max(1));
+
+        let params = FormationParams {
+            shape,
+            count,
+            spacing: self.spacing,
+            center: (0.5, 0.25),
+            mix: self.mix.clone(),
+        };
+
+        WaveDef {
+            name: format!("{:?} of {}", shape, count),
+            modifiers: self.modifiers,
+            enemies: FormationGenerator::generate(&params, aspect),
+        }
+    }
+}
+
+pub struct FormationGenerator;
+
+impl FormationGenerator {
+    // `aspect` is board width over height, used so shapes keep their
+    // proportions on a board that is taller than it is wide.
+    pub fn generate(params: &FormationParams, aspect: f32) -> Vec<WaveEnemy> {
+        let mut points: Vec<(f32, f32)> = Self::points(params.shape, params.count)
+            .into_iter()
+            .map(|(dx, dy)| {
+                let x = params.center.0 + dx * params.spacing;
+                let y = params.center.1 + dy * params.spacing * aspect;
+                (x.clamp(FIELD_X.0, FIELD_X.1), y.clamp(FIELD_Y.0, FIELD_Y.1))
+            })
+            .collect();
+
+        // Types are handed out top to bottom so the mix forms bands.
+        points.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.total_cmp(&b.0)));
+
+        let count = points.len();
+        points.into_iter()
+            .enumerate()
+            .filter_map(|(i, (x, y))| {
+                Self::type_at(&params.mix, i, count).map(|enemy_type| WaveEnemy {
+                    enemy_type: enemy_type.to_string(),
+                    x,
+                    y,
+                    delay: 0.0,
+                })
+            })
+            .collect()
+    }
+
+    fn type_at(mix: &[(String, u32)], index: usize, count: usize) -> Option<&str> {
+        let total: u32 = mix.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let target = (index as f32 + 0.5) / count as f32 * total as f32;
+        let mut acc = 0.0;
+        for (name, weight) in mix {
+            acc += *weight as f32;
+            if target < acc {
+                return Some(name);
+            }
+        }
+        mix.last().map(|(name, _)| name.as_str())
+    }
+
+    // Offsets from the centre in units of spacing.
+    fn points(shape: FormationShape, count: usize) -> Vec<(f32, f32)> {
+        let n = count as f32;
+
+        match shape {
+            FormationShape::Grid => {
+                let columns = (n.sqrt() * 1.5).ceil().max(1.0) as usize;
+                let rows = count.div_ceil(columns);
+                (0..count).map(|i| {
+                    let (row, column) = (i / columns, i % columns);
+                    let in_row = if row + 1 == rows { count - row * columns } else { columns };
+                    (column as f32 - (in_row as f32 - 1.0) / 2.0, row as f32 - (rows as f32 - 1.0) / 2.0)
+                }).collect()
+            }
+            FormationShape::V => {
+                (0..count).map(|i| {
+                    let arm = i.div_ceil(2) as f32;
+                    let side = if i % 2 == 0 { 1.0 } else { -1.0 };
+                    (side * arm, -arm * 0.8 + n / 4.0)
+                }).collect()
+            }
+            FormationShape::Diamond => {
+                let radius = (n / 4.0).max(1.0);
+                let corners = [(0.0, -radius), (radius, 0.0), (0.0, radius), (-radius, 0.0)];
+                (0..count).map(|i| {
+                    let t = i as f32 / n * 4.0;
+                    let edge = t.floor() as usize % 4;
+                    let f = t.fract();
+                    let (a, b) = (corners[edge], corners[(edge + 1) % 4]);
+                    (a.0 + (b.0 - a.0) * f, a.1 + (b.1 - a.1) * f)
+                }).collect()
+            }
+            FormationShape::Circle => {
+                let radius = (n / (2.0 * PI)).max(1.0);
+                (0..count).map(|i| {
+                    let angle = 2.0 * PI * i as f32 / n - PI / 2.0;
+                    (radius * angle.cos(), radius * angle.sin())
+                }).collect()
+            }
+            FormationShape::SineRow => {
+                let span = (n - 1.0).max(1.0);
+                (0..count).map(|i| {
+                    let x = i as f32 - span / 2.0;
+                    (x, (2.0 * PI * i as f32 / span).sin())
+                }).collect()
+            }
+            FormationShape::Spiral => {
+                (0..count).map(|i| {
+                    let angle = i as f32 * 0.9;
+                    let radius = 0.4 + i as f32 * 0.2;
+                    (radius * angle.cos(), radius * angle.sin())
+                }).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn params(shape: FormationShape, count: usize, spacing: f32, center: (f32, f32)) -> FormationParams {
+        FormationParams { shape, count, spacing, center, mix: vec![("b2".to_string(), 1)] }
+    }
+
+    fn span(enemies: &[WaveEnemy]) -> f32 {
+        let xs = enemies.iter().map(|enemy| enemy.x);
+        xs.clone().fold(f32::MIN, f32::max) - xs.fold(f32::MAX, f32::min)
+    }
+
+    #[test]
+    fn every_shape_places_count_enemies_inside_the_field() {
+        for shape in FormationShape::ALL {
+            for count in [1, 6, 20, 40] {
+                let enemies = FormationGenerator::generate(&params(shape, count, 0.12, (0.5, 0.25)), 0.7);
+                assert_eq!(enemies.len(), count, "{:?} of {}", shape, count);
+                for enemy in &enemies {
+                    assert!((FIELD_X.0..=FIELD_X.1).contains(&enemy.x), "{:?} x {}", shape, enemy.x);
+                    assert!((FIELD_Y.0..=FIELD_Y.1).contains(&enemy.y), "{:?} y {}", shape, enemy.y);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_pair_sits_one_spacing_apart_around_the_center() {
+        let enemies = FormationGenerator::generate(&params(FormationShape::Grid, 2, 0.1, (0.4, 0.3)), 1.0);
+        let xs: Vec<f32> = enemies.iter().map(|enemy| enemy.x).collect();
+        assert!((xs[0] - 0.35).abs() < 1e-5 && (xs[1] - 0.45).abs() < 1e-5, "{:?}", xs);
+        assert!(enemies.iter().all(|enemy| (enemy.y - 0.3).abs() < 1e-5));
+    }
+
+    #[test]
+    fn spacing_scales_the_formation() {
+        for shape in FormationShape::ALL {
+            let narrow = FormationGenerator::generate(&params(shape, 6, 0.03, (0.45, 0.25)), 1.0);
+            let wide = FormationGenerator::generate(&params(shape, 6, 0.06, (0.45, 0.25)), 1.0);
+            assert!((span(&wide) - span(&narrow) * 2.0).abs() < 1e-4, "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn endless_waves_grow_up_to_the_players_limit() {
+        let generated = GeneratedWaves {
+            start_count: 6,
+            growth: 2,
+            max_count: 20,
+            spacing: 0.05,
+            shapes: vec![FormationShape::Grid],
+            mix: vec![("b2".to_string(), 1)],
+            modifiers: WaveModifiers::default(),
+        };
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(generated.wave(1, None, 1.0, &mut rng).enemies.len(), 8);
+        assert_eq!(generated.wave(20, None, 1.0, &mut rng).enemies.len(), 20);
+        assert_eq!(generated.wave(20, Some(10), 1.0, &mut rng).enemies.len(), 10);
+        assert_eq!(generated.wave(0, Some(4), 1.0, &mut rng).enemies.len(), 4);
+    }
+}
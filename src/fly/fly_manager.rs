@@ -1,160 +1,236 @@
-use pelican_ui::Context;
-use pelican_ui_std::Offset;
-use pelican_game_engine::{Sprite, Gameboard};
-use std::sync::{Mutex, OnceLock};
-use std::time::Instant;
-
-use crate::fly::fly_state::{EnemyState, EnemyGlobalState};
-use crate::fly::fly_patterns::EnemyPatterns;
+use std::collections::BTreeMap;
+
+use crate::fly::fly_state::{ClearedWave, EnemyBehaviour, EnemyState, EnemyGlobalState, Entry, PendingEnemy};
+use crate::fly::fly_beam::EnemyBeams;
+use crate::fly::fly_behaviour::EnemyBehaviours;
+use crate::fly::fly_boss::EnemyBoss;
+use crate::fly::fly_challenge::EnemyChallenge;
+use crate::fly::fly_entry::{EnemyEntries, ENTRY_GROUP_SIZE, ENTRY_STAGGER};
+use crate::fly::fly_waves::{WaveModifiers, WaveSet};
 use crate::fly::fly_bullets::EnemyBullets;
 use crate::fly::fly_movement::EnemyMovement;
+use crate::fly::fly_dives::EnemyDives;
 
 use crate::fly::fly_utils;
-
-static ENEMY_GLOBALS: OnceLock<Mutex<EnemyGlobalState>> = OnceLock::new();
-
+use crate::fly::fly_types::EnemyTypes;
+use crate::world::{Body, EntityKind, World};
+use rand::rngs::StdRng;
 
 pub struct EnemyManager;
 
 impl EnemyManager {
-    pub fn initialize() {
-        ENEMY_GLOBALS.set(Mutex::new(EnemyGlobalState::default())).ok();
-    }
-
-    pub fn get_current_state() -> EnemyState {
-        ENEMY_GLOBALS
-            .get()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .enemy_state
-            .clone()
-    }
-
-    pub fn get_wave_count() -> u32 {
-        ENEMY_GLOBALS
-            .get()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .wave_count
+    pub fn get_wave_count(globals: &EnemyGlobalState) -> u32 {
+        globals.wave_count
     }
 
-    pub fn create_enemies(ctx: &mut Context, board: &mut Gameboard) {
-        let (board_width, board_height) = board.0.size(ctx);
+    pub fn create_enemies(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, waves: &WaveSet, rng: &mut StdRng, time: f32) {
+        let (board_width, board_height) = world.field;
 
-        let mut globals = ENEMY_GLOBALS.get().unwrap().lock().unwrap();
+        if globals.enemy_state != EnemyState::Initial {
+            globals.wave_count += 1;
+        }
+        globals.enemy_state = EnemyState::Active;
+        globals.wave_started = time;
 
         globals.base_positions.clear();
         globals.enemy_last_shot_times.clear();
+        globals.pending.clear();
+        globals.dives.clear();
+        globals.entries.clear();
+        globals.agents.clear();
+        globals.boss = None;
+        globals.challenge = None;
+        globals.capture = None;
+        globals.last_dive = time;
+        globals.last_beam = time;
+
+        if EnemyBoss::is_boss_wave(globals, types) {
+            globals.modifiers = WaveModifiers::default();
+            EnemyBoss::queue_boss(globals, world, types, time);
+            Self::spawn_pending(globals, world, types, time);
+            return;
+        }
+
+        if EnemyChallenge::is_challenge_wave(globals) {
+            globals.modifiers = WaveModifiers::default();
+            EnemyChallenge::queue_stage(globals, world, types, time);
+            return;
+        }
+
+        let aspect = if board_height > 0.0 { board_width / board_height } else { 1.0 };
+        let wave = waves.wave(globals.wave_count as usize, globals.max_targets, aspect, rng);
+        globals.modifiers = wave.modifiers;
 
-        let enemies = match globals.enemy_state {
-            EnemyState::Initial => {
-                globals.enemy_state = EnemyState::Pattern1;
-                EnemyPatterns::get_initial_pattern(board_width, board_height)
-            },
-            EnemyState::Pattern1 => {
-                globals.enemy_state = EnemyState::Pattern2;
-                globals.wave_count += 1;
-                EnemyPatterns::get_pattern_1(board_width, board_height)
-            },
-            EnemyState::Pattern2 => {
-                globals.enemy_state = EnemyState::Pattern3;
-                globals.wave_count += 1;
-                EnemyPatterns::get_pattern_2(board_width, board_height)
-            },
-            EnemyState::Pattern3 => {
-                globals.enemy_state = EnemyState::Pattern1;
-                globals.wave_count += 1;
-                EnemyPatterns::get_pattern_3(board_width, board_height)
-            },
-            EnemyState::AllDestroyed => {
-                globals.wave_count += 1;
-
-                let (new_state, enemies) = match globals.wave_count % 3 {
-                    1 => (EnemyState::Pattern1, EnemyPatterns::get_pattern_1(board_width, board_height)),
-                    2 => (EnemyState::Pattern2, EnemyPatterns::get_pattern_2(board_width, board_height)),
-                    0 => (EnemyState::Pattern3, EnemyPatterns::get_pattern_3(board_width, board_height)),
-                    _ => (EnemyState::Pattern1, EnemyPatterns::get_pattern_1(board_width, board_height)),
-                };
-                
-                globals.enemy_state = new_state;
-                enemies
-            },
-        };
-
-        for (id, image, x, y) in enemies {
-            let sprite = Sprite::new(ctx, id, image, (50.0, 50.0), (Offset::Static(x), Offset::Static(y)));
-            board.insert_sprite(ctx, sprite);
-
-            globals.base_positions.insert(id.to_string(), (x, y));
-
-            if fly_utils::is_enemy(id) {
-                globals.enemy_last_shot_times.insert(id.to_string(), Instant::now());
+        let mut per_type: BTreeMap<&str, u32> = BTreeMap::new();
+        for (index, enemy) in wave.enemies.iter().enumerate() {
+            let Some(type_id) = types.id_of(&enemy.enemy_type) else {
+                println!("Unknown enemy type {} in wave {}", enemy.enemy_type, wave.name);
+                continue;
+            };
+
+            let n = per_type.entry(&enemy.enemy_type).or_insert(0);
+            *n += 1;
+
+            let group = index / ENTRY_GROUP_SIZE;
+            globals.pending.push(PendingEnemy {
+                id: format!("{}_{}", enemy.enemy_type, n),
+                type_id,
+                position: (enemy.x * board_width, enemy.y * board_height),
+                spawn_at: time + enemy.delay + group as f32 * ENTRY_STAGGER,
+                entry_side: EnemyEntries::side_for_group(group),
+            });
+        }
+
+        Self::spawn_pending(globals, world, types, time);
+    }
+
+    // Brings in every waiting enemy whose entry delay has passed. They start
+    // off-screen and fly to their slot from there.
+    pub fn spawn_pending(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        let (ready, waiting): (Vec<PendingEnemy>, Vec<PendingEnemy>) = std::mem::take(&mut globals.pending)
+            .into_iter()
+            .partition(|enemy| enemy.spawn_at <= time);
+        globals.pending = waiting;
+
+        for enemy in ready {
+            let Some(enemy_type) = types.get(enemy.type_id) else { continue };
+            let from = EnemyEntries::start_position(enemy.entry_side, world.field, enemy_type.size);
+            let body = Body::new(EntityKind::Enemy(enemy.type_id), &enemy_type.sprite, from, enemy_type.size)
+                .with_hp(enemy_type.hp);
+            world.insert(&enemy.id, body);
+
+            globals.entries.insert(enemy.id.clone(), Entry { from, side: enemy.entry_side });
+            EnemyBehaviours::set(globals, &enemy.id, EnemyBehaviour::Entering, time);
+            globals.base_positions.insert(enemy.id.clone(), enemy.position);
+            globals.enemy_last_shot_times.insert(enemy.id, time);
+        }
+    }
+
+    // Starts the next wave once this one is cleared, and reports how the
+    // cleared one went.
+    pub fn check_and_manage_enemy_state(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, waves: &WaveSet, rng: &mut StdRng, time: f32) -> Option<ClearedWave> {
+        Self::spawn_pending(globals, world, types, time);
+
+        let enemy_count = fly_utils::count_active_enemies(world);
+
+        if enemy_count == 0
+            && globals.pending.is_empty()
+            && !EnemyChallenge::has_queued(globals)
+            && globals.enemy_state != EnemyState::AllDestroyed
+        {
+            globals.enemy_state = EnemyState::AllDestroyed;
+            let elapsed = time - globals.wave_started;
+            let challenge = EnemyChallenge::result(globals);
+            if let Some((hits, total)) = challenge {
+                globals.challenge_hits += hits;
+                globals.challenge_total += total;
             }
+
+            EnemyBullets::clear_all_enemy_bullets(world);
+
+            Self::create_enemies(globals, world, types, waves, rng, time);
+            return Some(ClearedWave { elapsed, challenge });
         }
 
-        println!("Created {} enemies for wave {}", globals.base_positions.len(), globals.wave_count);
+        None
     }
 
-    pub fn check_and_manage_enemy_state(ctx: &mut Context, board: &mut Gameboard) {
-        let enemy_count = fly_utils::count_active_enemies(board);
+    pub fn update_enemy_pulse(globals: &mut EnemyGlobalState, world: &mut World, dt: f32) {
+        EnemyMovement::update_enemy_pulse(world, &mut globals.pulse_time, &globals.base_positions, &globals.agents, dt);
+    }
 
-        if enemy_count == 0 {
-            let current_state = {
-                let globals = ENEMY_GLOBALS.get().unwrap().lock().unwrap();
-                globals.enemy_state.clone()
-            };
+    // Runs the per-enemy behaviour timers and removes enemies that have
+    // finished dying.
+    pub fn update_behaviours(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        for enemy_id in EnemyBehaviours::update(globals, world, types, time) {
+            world.remove(&enemy_id);
+            Self::remove_enemy_from_base_positions(globals, &enemy_id);
+        }
+    }
 
-            if current_state != EnemyState::AllDestroyed {
-                {
-                    let mut globals = ENEMY_GLOBALS.get().unwrap().lock().unwrap();
-                    globals.enemy_state = EnemyState::AllDestroyed;
-                }
+    pub fn kill_behaviour_of(globals: &EnemyGlobalState, enemy_id: &str) -> Option<EnemyBehaviour> {
+        EnemyBehaviours::underlying(globals, enemy_id)
+    }
 
-                EnemyBullets::clear_all_enemy_bullets(ctx, board);
-                
-                Self::create_enemies(ctx, board);
-            }
+    pub fn hit_enemy(globals: &mut EnemyGlobalState, enemy_id: &str, time: f32) {
+        EnemyBehaviours::hit(globals, enemy_id, time);
+    }
+
+    pub fn kill_enemy(globals: &mut EnemyGlobalState, enemy_id: &str, time: f32) {
+        if EnemyBehaviours::underlying(globals, enemy_id) == Some(EnemyBehaviour::Passing) {
+            EnemyChallenge::record_hit(globals);
         }
+        EnemyBeams::captor_destroyed(globals, enemy_id);
+        EnemyBehaviours::kill(globals, enemy_id, time);
     }
 
-    pub fn update_enemy_pulse(ctx: &mut Context, board: &mut Gameboard) {
-        let mut globals = ENEMY_GLOBALS.get().unwrap().lock().unwrap();
-        let base_positions = globals.base_positions.clone(); 
-        EnemyMovement::update_enemy_pulse(ctx, board, &mut globals.pulse_time, &base_positions);
+    pub fn update_entries(globals: &EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        EnemyEntries::update_entries(globals, world, types, time);
     }
 
-    pub fn update_enemy_shooting(ctx: &mut Context, board: &mut Gameboard) {
-        let mut globals = ENEMY_GLOBALS.get().unwrap().lock().unwrap();
-        EnemyBullets::update_enemy_shooting(ctx, board, &mut globals.enemy_last_shot_times);
+    pub fn update_dives(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, rng: &mut StdRng, time: f32, bullet_speed: f32, launch: bool) {
+        EnemyDives::update_dives(globals, world, types, rng, time, bullet_speed, launch);
     }
 
-    pub fn update_enemy_bullets(ctx: &mut Context, board: &mut Gameboard) -> Vec<String> {
-        EnemyBullets::update_enemy_bullets(ctx, board)
+    pub fn update_beams(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, rng: &mut StdRng, time: f32, launch: bool) {
+        EnemyBeams::update(globals, world, types, rng, time, launch);
     }
 
-    pub fn get_active_enemy_bullets(board: &mut Gameboard, ctx: &mut Context) -> Vec<(String, (f32, f32), (f32, f32))> {
-        EnemyBullets::get_active_enemy_bullets(board, ctx)
+    pub fn start_capture(globals: &mut EnemyGlobalState, captor: &str, time: f32) {
+        EnemyBeams::start_capture(globals, captor, time);
     }
 
-    pub fn remove_enemy_from_base_positions(enemy_id: &str) {
-        if let Some(globals) = ENEMY_GLOBALS.get() {
-            let mut globals = globals.lock().unwrap();
-            globals.base_positions.remove(enemy_id);
-            globals.enemy_last_shot_times.remove(enemy_id);
+    pub fn captor_position(globals: &EnemyGlobalState, world: &World) -> Option<((f32, f32), (f32, f32))> {
+        EnemyBeams::captor_position(globals, world)
+    }
+
+    pub fn hold_ship(globals: &mut EnemyGlobalState, world: &mut World, size: (f32, f32)) {
+        EnemyBeams::hold_ship(globals, world, size);
+    }
+
+    pub fn take_freed_ship(globals: &mut EnemyGlobalState) -> bool {
+        EnemyBeams::take_freed_ship(globals)
+    }
+
+    // Flies the challenge stage and removes enemies that got away.
+    pub fn update_challenge(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32) {
+        for enemy_id in EnemyChallenge::update(globals, world, types, time) {
+            world.remove(&enemy_id);
+            Self::remove_enemy_from_base_positions(globals, &enemy_id);
         }
     }
 
-    pub fn is_enemy(sprite_id: &str) -> bool {
-        fly_utils::is_enemy(sprite_id)
+    // Challenge stage hits and enemies over the run, including any stage
+    // still in play.
+    pub fn challenge_results(globals: &EnemyGlobalState) -> (u32, u32) {
+        let (hits, total) = EnemyChallenge::result(globals).unwrap_or((0, 0));
+        (globals.challenge_hits + hits, globals.challenge_total + total)
+    }
+
+    pub fn update_boss(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, time: f32, bullet_speed: f32) {
+        EnemyBoss::update(globals, world, types, time, bullet_speed);
     }
 
-    pub fn is_enemy_bullet(sprite_id: &str) -> bool {
-        fly_utils::is_enemy_bullet(sprite_id)
+    pub fn boss_health(globals: &EnemyGlobalState, world: &World, types: &EnemyTypes) -> Option<(u32, u32)> {
+        EnemyBoss::health(globals, world, types)
     }
 
-    pub fn is_tiki(sprite_id: &str) -> bool {
-        fly_utils::is_tiki(sprite_id)
+    pub fn update_enemy_shooting(globals: &mut EnemyGlobalState, world: &mut World, types: &EnemyTypes, rng: &mut StdRng, time: f32, bullet_speed: f32) {
+        EnemyBullets::update_enemy_shooting(globals, world, types, rng, time, bullet_speed);
+    }
+
+    pub fn get_active_enemy_bullets(world: &World) -> Vec<(String, (f32, f32), (f32, f32))> {
+        EnemyBullets::get_active_enemy_bullets(world)
+    }
+
+    pub fn remove_enemy_from_base_positions(globals: &mut EnemyGlobalState, enemy_id: &str) {
+        globals.base_positions.remove(enemy_id);
+        globals.enemy_last_shot_times.remove(enemy_id);
+        globals.dives.remove(enemy_id);
+        globals.entries.remove(enemy_id);
+        globals.agents.remove(enemy_id);
+        if let Some(challenge) = globals.challenge.as_mut() {
+            challenge.paths.remove(enemy_id);
+        }
     }
-}
\ No newline at end of file
+}
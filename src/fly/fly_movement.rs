@@ -1,20 +1,21 @@
-use pelican_ui::Context;
-use pelican_game_engine::Gameboard;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-const PULSE_AMPLITUDE: f32 = 5.0;
-const PULSE_SPEED: f32 = 0.1;
+use crate::fly::fly_state::{EnemyAgent, EnemyBehaviour};
+use crate::world::World;
+
+use super::{PULSE_AMPLITUDE, PULSE_SPEED};
 
 pub struct EnemyMovement;
 
 impl EnemyMovement {
     pub fn update_enemy_pulse(
-        ctx: &mut Context, 
-        board: &mut Gameboard,
+        world: &mut World,
         pulse_time: &mut f32,
-        base_positions: &HashMap<String, (f32, f32)>
+        base_positions: &BTreeMap<String, (f32, f32)>,
+        agents: &BTreeMap<String, EnemyAgent>,
+        dt: f32,
     ) {
-        *pulse_time += PULSE_SPEED;
+        *pulse_time += PULSE_SPEED * dt;
 
         let pulse_scale = (pulse_time.sin() + 1.0) * 0.5;
         let pulse_offset = pulse_scale * PULSE_AMPLITUDE;
@@ -27,7 +28,12 @@ impl EnemyMovement {
         let center_y = base_positions.values().map(|(_, y)| *y).sum::<f32>() / base_positions.len() as f32;
 
         for (enemy_id, &(base_x, base_y)) in base_positions.iter() {
-            if let Some(sprite) = board.2.get_mut(enemy_id) {
+            // Only enemies sitting in formation breathe with it.
+            if agents.get(enemy_id).is_some_and(|agent| agent.behaviour != EnemyBehaviour::InFormation) {
+                continue;
+            }
+
+            if let Some(body) = world.get_mut(enemy_id) {
                 let dx = base_x - center_x;
                 let dy = base_y - center_y;
 
@@ -41,8 +47,7 @@ impl EnemyMovement {
                 let pulse_x = norm_dx * pulse_offset;
                 let pulse_y = norm_dy * pulse_offset;
 
-                sprite.adjustments().0 = pulse_x;
-                sprite.adjustments().1 = pulse_y;
+                body.position = (base_x + pulse_x, base_y + pulse_y);
             }
         }
     }
@@ -1,31 +1,171 @@
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::BTreeMap;
+
+use crate::fly::fly_types::EnemyTypeId;
+use crate::fly::fly_waves::WaveModifiers;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum EnemyState {
     Initial,
-    Pattern1,
-    Pattern2,
-    Pattern3,
+    Active,
     AllDestroyed,
 }
 
+// What a single enemy is doing. Timed behaviours run for as long as the
+// enemy type says and then move on by themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyBehaviour {
+    Entering,
+    InFormation,
+    Diving,
+    Returning,
+    Hit,
+    Dying,
+    // Flying a challenge stage path. Lasts until the path ends.
+    Passing,
+    // Hovering at the bottom of a dive with the tractor beam on.
+    Beaming,
+}
+
+// `resume` is the behaviour a hit interrupted and when it had started.
+#[derive(Debug, Clone)]
+pub struct EnemyAgent {
+    pub behaviour: EnemyBehaviour,
+    pub since: f32,
+    pub resume: Option<(EnemyBehaviour, f32)>,
+}
+
+// An enemy from the current wave still waiting out its entry delay.
+#[derive(Debug, Clone)]
+pub struct PendingEnemy {
+    pub id: String,
+    pub type_id: EnemyTypeId,
+    pub position: (f32, f32),
+    pub spawn_at: f32,
+    pub entry_side: f32,
+}
+
+// The path of an enemy flying in from off-screen to its slot.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub from: (f32, f32),
+    pub side: f32,
+}
+
+// The path of an enemy out of formation on a dive. `side` is which way it
+// swoops out, `target` is where the player was when it left. A `beam` dive
+// stops short above the player to use the tractor beam instead of shooting.
+#[derive(Debug, Clone)]
+pub struct Dive {
+    pub target: (f32, f32),
+    pub side: f32,
+    pub shot: bool,
+    pub beam: bool,
+}
+
+// A tractor beam capture. The captor pulls the player in, then carries the
+// ship around with it (`holding`) until it is shot down.
+#[derive(Debug, Clone)]
+pub struct Capture {
+    pub captor: String,
+    pub holding: bool,
+}
+
+// The single large enemy of a boss wave. `phase` picks its attack pattern
+// and `minion_count` counts every minion it has called so far.
+#[derive(Debug, Clone)]
+pub struct BossState {
+    pub id: String,
+    pub phase: usize,
+    pub last_attack: f32,
+    pub last_minions: f32,
+    pub minion_count: u32,
+}
+
+// An enemy of a challenge stage waiting for its turn to fly in.
+#[derive(Debug, Clone)]
+pub struct QueuedPass {
+    pub id: String,
+    pub type_id: EnemyTypeId,
+    pub path: [(f32, f32); 4],
+    pub spawn_at: f32,
+}
+
+// A challenge stage in play. `paths` holds the enemies flying right now,
+// `hits` how many of `total` the player has shot down.
+#[derive(Debug, Clone)]
+pub struct ChallengeState {
+    pub queue: Vec<QueuedPass>,
+    pub paths: BTreeMap<String, [(f32, f32); 4]>,
+    pub hits: u32,
+    pub total: u32,
+}
+
+// How a wave went, reported once it's cleared. `challenge` is hits and total
+// when the wave was a challenge stage.
+#[derive(Debug, Clone, Copy)]
+pub struct ClearedWave {
+    pub elapsed: f32,
+    pub challenge: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Clone)]
 pub struct EnemyGlobalState {
-    pub base_positions: HashMap<String, (f32, f32)>,
+    pub base_positions: BTreeMap<String, (f32, f32)>,
     pub pulse_time: f32,
-    pub enemy_last_shot_times: HashMap<String, Instant>,
+    pub enemy_last_shot_times: BTreeMap<String, f32>,
     pub enemy_state: EnemyState,
     pub wave_count: u32,
+    pub wave_started: f32,
+    pub pending: Vec<PendingEnemy>,
+    pub modifiers: WaveModifiers,
+    // Most enemies a generated wave may hold, None for the wave set's own
+    // maximum.
+    pub max_targets: Option<usize>,
+    pub dives: BTreeMap<String, Dive>,
+    pub last_dive: f32,
+    pub entries: BTreeMap<String, Entry>,
+    pub agents: BTreeMap<String, EnemyAgent>,
+    pub boss: Option<BossState>,
+    // Every this many waves is a boss wave, 0 for never.
+    pub boss_every: u32,
+    pub challenge: Option<ChallengeState>,
+    // Every this many waves is a challenge stage, 0 for never.
+    pub challenge_every: u32,
+    // Challenge stage hits and enemies over the whole run.
+    pub challenge_hits: u32,
+    pub challenge_total: u32,
+    pub capture: Option<Capture>,
+    // Set when a captor holding the player's ship is destroyed, until the
+    // ship is handed back.
+    pub ship_freed: bool,
+    pub last_beam: f32,
 }
 
 impl Default for EnemyGlobalState {
     fn default() -> Self {
         Self {
-            base_positions: HashMap::new(),
+            base_positions: BTreeMap::new(),
             pulse_time: 0.0,
-            enemy_last_shot_times: HashMap::new(),
+            enemy_last_shot_times: BTreeMap::new(),
             enemy_state: EnemyState::Initial,
             wave_count: 0,
+            wave_started: 0.0,
+            pending: Vec::new(),
+            modifiers: WaveModifiers::default(),
+            max_targets: None,
+            dives: BTreeMap::new(),
+            last_dive: 0.0,
+            entries: BTreeMap::new(),
+            agents: BTreeMap::new(),
+            boss: None,
+            boss_every: 0,
+            challenge: None,
+            challenge_every: 0,
+            challenge_hits: 0,
+            challenge_total: 0,
+            capture: None,
+            ship_freed: false,
+            last_beam: 0.0,
         }
     }
-}
\ No newline at end of file
+}
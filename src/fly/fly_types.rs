@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use std::fs;
+use std::sync::OnceLock;
+
+use crate::fly::fly_boss::{BOSS_TYPE, MINION_TYPE};
+use crate::user_data::UserData;
+
+const ENEMY_TYPES_FILE: &str = "enemy_types.json";
+
+const BUNDLED_ENEMY_TYPES: &str = include_str!("../../data/enemy_types.json");
+
+static ENEMY_TYPES: OnceLock<EnemyTypes> = OnceLock::new();
+
+// Index into `EnemyTypes`. Bodies carry this rather than the name so the
+// entity kind stays `Copy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnemyTypeId(pub usize);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnemyType {
+    pub name: String,
+    pub sprite: String,
+    // Shown once the enemy has taken a hit but is still alive.
+    #[serde(default)]
+    pub damaged_sprite: Option<String>,
+    pub size: (f32, f32),
+    pub hp: u32,
+    pub score: u32,
+    // Worth this instead of `score` when shot out of formation on a dive.
+    pub dive_score: u32,
+    pub fire_cooldown: f32,
+    pub shot_chance: f32,
+    pub bullet_sprite: String,
+    pub bullet_size: (f32, f32),
+    // Seconds spent in each timed behaviour.
+    pub entry_duration: f32,
+    pub dive_duration: f32,
+    pub return_duration: f32,
+    pub hit_duration: f32,
+    pub dying_duration: f32,
+    // Seconds the enemy holds its tractor beam over the player, for the
+    // types that dive down with one.
+    #[serde(default)]
+    pub tractor_beam: Option<f32>,
+}
+
+impl EnemyType {
+    fn sprites(&self) -> impl Iterator<Item = &str> {
+        [Some(&self.sprite), self.damaged_sprite.as_ref(), Some(&self.bullet_sprite)]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EnemyTypes {
+    types: Vec<EnemyType>,
+}
+
+impl Default for EnemyTypes {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+impl EnemyTypes {
+    // The table every simulation plays with, loaded the first time it is
+    // asked for and kept for the rest of the run.
+    pub fn shared() -> &'static EnemyTypes {
+        ENEMY_TYPES.get_or_init(Self::load)
+    }
+
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED_ENEMY_TYPES).expect("bundled enemy_types.json is invalid")
+    }
+
+    // The user's enemy table if there is one that reads and validates,
+    // otherwise the bundled table. Having no table at all is the usual case
+    // and not worth a message.
+    pub fn load() -> Self {
+        let path = UserData::path(ENEMY_TYPES_FILE);
+        let Ok(json) = fs::read_to_string(&path) else { return Self::bundled() };
+
+        let loaded = Self::from_json(&json)
+            .map_err(|e| e.to_string())
+            .and_then(|types| types.validate(&Self::bundled()).map(|_| types));
+
+        loaded.unwrap_or_else(|e| {
+            eprintln!("Using bundled enemy types, {:?}: {}", path, e);
+            Self::bundled()
+        })
+    }
+
+    // Every type needs HP to be hit at all, the boss and its minions are
+    // looked up by name, and only the art the bundled table uses is packed
+    // with the game.
+    pub fn validate(&self, bundled: &EnemyTypes) -> Result<(), String> {
+        let sprites: Vec<&str> = bundled.types.iter().flat_map(EnemyType::sprites).collect();
+
+        for enemy_type in &self.types {
+            if enemy_type.hp == 0 {
+                return Err(format!("{} has no hp", enemy_type.name));
+            }
+            if let Some(sprite) = enemy_type.sprites().find(|sprite| !sprites.contains(sprite)) {
+                return Err(format!("{} uses unknown sprite {}", enemy_type.name, sprite));
+            }
+        }
+
+        for name in [BOSS_TYPE, MINION_TYPE] {
+            if self.id_of(name).is_none() {
+                return Err(format!("no {} type", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let types: Vec<EnemyType> = serde_json::from_str(json)?;
+        Ok(Self { types })
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<EnemyTypeId> {
+        self.types.iter().position(|t| t.name == name).map(EnemyTypeId)
+    }
+
+    pub fn get(&self, id: EnemyTypeId) -> Option<&EnemyType> {
+        self.types.get(id.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edited(edit: impl Fn(&mut Vec<EnemyType>)) -> EnemyTypes {
+        let mut types = EnemyTypes::bundled();
+        edit(&mut types.types);
+        types
+    }
+
+    #[test]
+    fn the_bundled_table_is_valid() {
+        assert!(EnemyTypes::bundled().validate(&EnemyTypes::bundled()).is_ok());
+    }
+
+    #[test]
+    fn tables_that_would_break_play_are_rejected() {
+        let bundled = EnemyTypes::bundled();
+        let no_hp = edited(|types| types[0].hp = 0);
+        let no_boss = edited(|types| types.retain(|t| t.name != BOSS_TYPE));
+        let no_minion = edited(|types| types.retain(|t| t.name != MINION_TYPE));
+        let bad_sprite = edited(|types| types[0].sprite = "missing".to_string());
+
+        for types in [no_hp, no_boss, no_minion, bad_sprite] {
+            assert!(types.validate(&bundled).is_err());
+        }
+    }
+
+    #[test]
+    fn an_unknown_id_is_not_found() {
+        let types = EnemyTypes::bundled();
+        assert!(types.get(EnemyTypeId(types.types.len())).is_none());
+    }
+}
@@ -1,23 +1,15 @@
-pub fn is_enemy(sprite_id: &str) -> bool {
-    (sprite_id.starts_with("b2_") || 
-     sprite_id.starts_with("tiki_") || 
-     sprite_id.starts_with("northrop_")) &&
-    !sprite_id.starts_with("player") &&
-    !sprite_id.starts_with("bullet_") &&
-    !sprite_id.starts_with("enemy_bullet_") &&
-    !sprite_id.starts_with("explosion_")
-}
+use crate::world::{EntityKind, World};
 
-pub fn is_enemy_bullet(sprite_id: &str) -> bool {
-    sprite_id.starts_with("enemy_bullet_")
+pub fn count_active_enemies(world: &World) -> usize {
+    world.count_of(EntityKind::is_enemy)
 }
 
-pub fn is_tiki(sprite_id: &str) -> bool {
-    sprite_id.starts_with("tiki_")
+// Point `t` of the way along a cubic Bezier curve, used for flight paths.
+pub fn bezier(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), t: f32) -> (f32, f32) {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+    )
 }
-
-pub fn count_active_enemies(board: &pelican_game_engine::Gameboard) -> usize {
-    let count = board.2.keys().filter(|id| is_enemy(id)).count();
-    println!("Active enemies: {}", count);
-    count
-}
\ No newline at end of file
@@ -0,0 +1,133 @@
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::fly::fly_formations::GeneratedWaves;
+use crate::user_data::UserData;
+
+const WAVES_DIR: &str = "waves";
+
+const BUNDLED_WAVE_SETS: [&str; 3] = [
+    include_str!("../../data/waves/classic.json"),
+    include_str!("../../data/waves/beginner.json"),
+    include_str!("../../data/waves/endless.json"),
+];
+
+// Multipliers applied to every enemy while the wave is up.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct WaveModifiers {
+    pub fire_rate: f32,
+    pub bullet_speed: f32,
+    pub score: f32,
+}
+
+impl Default for WaveModifiers {
+    fn default() -> Self {
+        Self { fire_rate: 1.0, bullet_speed: 1.0, score: 1.0 }
+    }
+}
+
+// `x` and `y` are fractions of the board width and height, `delay` is
+// seconds after the wave starts before this enemy appears.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveEnemy {
+    #[serde(rename = "type")]
+    pub enemy_type: String,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub delay: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveDef {
+    pub name: String,
+    #[serde(default)]
+    pub modifiers: WaveModifiers,
+    pub enemies: Vec<WaveEnemy>,
+}
+
+// A sequence of waves played in order. After the last one play carries on
+// with `generated` waves if there are any, otherwise from `loop_from`, so an
+// opening wave can be shown only once.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WaveSet {
+    pub name: String,
+    #[serde(default)]
+    pub loop_from: usize,
+    #[serde(default)]
+    pub waves: Vec<WaveDef>,
+    #[serde(default)]
+    pub generated: Option<GeneratedWaves>,
+}
+
+impl Default for WaveSet {
+    fn default() -> Self {
+        Self::bundled().remove(0)
+    }
+}
+
+impl WaveSet {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let mut set: WaveSet = serde_json::from_str(json)?;
+        set.loop_from = set.loop_from.min(set.waves.len().saturating_sub(1));
+        Ok(set)
+    }
+
+    pub fn is_playable(&self) -> bool {
+        !self.waves.is_empty() || self.generated.is_some()
+    }
+
+    pub fn wave(&self, index: usize, limit: Option<usize>, aspect: f32, rng: &mut StdRng) -> WaveDef {
+        let len = self.waves.len();
+        match &self.generated {
+            _ if index < len => self.waves[index].clone(),
+            Some(generated) => generated.wave(index - len, limit, aspect, rng),
+            None => {
+                let looped = len - self.loop_from;
+                self.waves[self.loop_from + (index - len) % looped].clone()
+            }
+        }
+    }
+
+    pub fn bundled() -> Vec<WaveSet> {
+        BUNDLED_WAVE_SETS.iter()
+            .map(|json| Self::from_json(json).expect("bundled wave set is invalid"))
+            .collect()
+    }
+
+    // Bundled sets first, then any *.json in the user data waves directory.
+    // A user set with the same name as a bundled one replaces it.
+    pub fn load_all() -> Vec<WaveSet> {
+        let mut sets = Self::bundled();
+
+        let dir = UserData::path(WAVES_DIR);
+        let Ok(entries) = fs::read_dir(&dir) else { return sets };
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let loaded = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| Self::from_json(&json).map_err(|e| e.to_string()));
+
+            match loaded {
+                Ok(set) if !set.is_playable() => println!("Skipping wave set {:?}: no waves", path),
+                Ok(set) => {
+                    println!("Loaded wave set '{}' from {:?}", set.name, path);
+                    sets.retain(|s| s.name != set.name);
+                    sets.push(set);
+                }
+                Err(e) => println!("Skipping wave set {:?}: {}", path, e),
+            }
+        }
+
+        sets
+    }
+}
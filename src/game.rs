@@ -3,38 +3,20 @@ use pelican_ui::drawable::{Align, Drawable, Component};
 use pelican_ui::layout::{Area, SizeRequest, Layout};
 use pelican_ui::{Context, Component};
 use pelican_ui_std::{Stack, Content, Header, Bumper, Page, Button, Offset, TextStyle, Text, AppPage, Size, Padding, Column, Wrap, Row, ButtonSize, ButtonWidth, ButtonStyle, ButtonState, IconButton, NavigateEvent, DataItem};
-use pelican_game_engine::{AspectRatio, Sprite, Gameboard, SpriteAction};
+use pelican_game_engine::{AspectRatio, Sprite, Gameboard};
 
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 pub use crate::fly::fly_manager::EnemyManager;
-pub use crate::fly::fly_state::{EnemyState, EnemyGlobalState};
-pub use crate::collision::CollisionManager;
 
-use crate::player::{PlayerManager, PlayerLives, LivesDisplayInfo, PlayerState, MovementDirection, KeysHeld, ServerMovement};
-use crate::server::{ServerEvent, GameServer, ServerEventHandler, GameAction};
+use crate::scanning::ScanningControl;
+use crate::sim::{PlayerAction, SimInput};
+use crate::server::{GameServer, ServerEventHandler, GameAction, ServerInfo, now_millis};
 
 use crate::settings::GameSettings;
-
-const EXPLOSION_DURATION: Duration = Duration::from_secs(2);
-const RESPAWN_DELAY: Duration = Duration::from_millis(500);
-const GAME_OVER_RESET_DELAY: Duration = Duration::from_secs(3);
-const LIFE_SPRITE_SIZE: (f32, f32) = (20.0, 20.0);
-const LIFE_SPRITE_SPACING: f32 = 35.0;
-const LIFE_SPRITE_START_X: f32 = 20.0;
-const LIFE_SPRITE_Y: f32 = 20.0;
-
-static mut EXPLOSIONS: Option<HashMap<String, Instant>> = None;
-static mut ENEMIES_CREATED: bool = false;
-static mut PLAYER_RESPAWN_TIME: Option<Instant> = None;
-static mut PLAYER_IS_DEAD: bool = false;
-static mut SCORE: u32 = 0;
-static mut SERVER_EVENT_HANDLER: Option<ServerEventHandler> = None;
-static mut GAME_SERVER: Option<GameServer> = None;
-static mut GAME_SETTINGS: Option<GameSettings> = None;
-static mut GAME_OVER_TIME: Option<Instant> = None;
-static mut GAME_IS_OVER: bool = false;
+use crate::session::GameSession;
+use crate::clock::SIM_STEP;
+use crate::phase::{GamePhase, GameCommand, GameCommandEvent, PauseItem, COUNTDOWN_DURATION, GAME_OVER_DURATION};
 
 pub struct SettingsButton;
 impl SettingsButton {
@@ -54,16 +36,13 @@ impl SettingsButton {
 
 
 #[derive(Debug, Component)]
-pub struct Settings(Stack, Page);
+pub struct Settings(Stack, Page, #[skip] Option<Box<Galaga>>, #[skip] u64, #[skip] GamePhase);
 impl OnEvent for Settings {
     fn on_event(&mut self, ctx: &mut Context, event: &mut dyn Event) -> bool {
         if let Some(AdjustPressureEvent(p)) = event.downcast_ref::<AdjustPressureEvent>() {
             let mut peak = &mut ctx.state().get_mut::<GameSettings>().unwrap().peak_min;
             if  *peak < 1000.0 {
                 ctx.state().get_mut::<GameSettings>().unwrap().peak_min += p;
-
-                println!("peak: {}",  ctx.state().get_mut::<GameSettings>().unwrap().peak_min);
-
                 *self.1.content().find_at::<DataItem>(0).unwrap().label() = format!("Touchpad Pressure: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().peak_min);
             }
         } else if event.downcast_ref::<ToggleFliesShoot>().is_some() {
@@ -98,6 +77,84 @@ impl OnEvent for Settings {
             let buttons = &mut self.1.content().find_at::<DataItem>(4).unwrap().buttons();
             let label = &mut buttons.as_mut().unwrap()[0].label().as_mut().unwrap().text().spans[0].text;
             *label = if player_invincible { "Turn Off".to_string() } else { "Turn On".to_string() };
+        } else if event.downcast_ref::<ToggleScanningMode>().is_some() {
+            let scanning_mode = !ctx.state().get_mut::<GameSettings>().unwrap().scanning_mode;
+            ctx.state().get_mut::<GameSettings>().unwrap().scanning_mode = scanning_mode;
+            let val = if scanning_mode {"Yes"} else {"No"};
+            *self.1.content().find_at::<DataItem>(5).unwrap().label() = format!("Single-Switch Scanning: {}", val);
+            let buttons = &mut self.1.content().find_at::<DataItem>(5).unwrap().buttons();
+            let label = &mut buttons.as_mut().unwrap()[0].label().as_mut().unwrap().text().spans[0].text;
+            *label = if scanning_mode { "Turn Off".to_string() } else { "Turn On".to_string() };
+        } else if let Some(AdjustScanIntervalEvent(d)) = event.downcast_ref::<AdjustScanIntervalEvent>() {
+            let interval = ctx.state().get_mut::<GameSettings>().unwrap().scan_interval + d;
+            ctx.state().get_mut::<GameSettings>().unwrap().set_scan_interval(interval);
+            *self.1.content().find_at::<DataItem>(6).unwrap().label() = format!("Scan Interval: {:.2}s", ctx.state().get_mut::<GameSettings>().unwrap().scan_interval);
+        } else if event.downcast_ref::<ToggleGradedBands>().is_some() {
+            let graded_bands = !ctx.state().get_mut::<GameSettings>().unwrap().graded_bands;
+            ctx.state().get_mut::<GameSettings>().unwrap().graded_bands = graded_bands;
+            let val = if graded_bands {"Yes"} else {"No"};
+            *self.1.content().find_at::<DataItem>(7).unwrap().label() = format!("Pressure Bands: {}", val);
+            let buttons = &mut self.1.content().find_at::<DataItem>(7).unwrap().buttons();
+            let label = &mut buttons.as_mut().unwrap()[0].label().as_mut().unwrap().text().spans[0].text;
+            *label = if graded_bands { "Turn Off".to_string() } else { "Turn On".to_string() };
+        } else if let Some(AdjustMoveBandEvent(p)) = event.downcast_ref::<AdjustMoveBandEvent>() {
+            let band = ctx.state().get_mut::<GameSettings>().unwrap().band_move_min + p;
+            ctx.state().get_mut::<GameSettings>().unwrap().set_band_move_min(band);
+            *self.1.content().find_at::<DataItem>(8).unwrap().label() = format!("Move Band Starts At: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().band_move_min);
+        } else if let Some(AdjustShootBandEvent(p)) = event.downcast_ref::<AdjustShootBandEvent>() {
+            let band = ctx.state().get_mut::<GameSettings>().unwrap().band_shoot_min + p;
+            ctx.state().get_mut::<GameSettings>().unwrap().set_band_shoot_min(band);
+            *self.1.content().find_at::<DataItem>(9).unwrap().label() = format!("Shoot Band Starts At: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().band_shoot_min);
+        } else if event.downcast_ref::<TickEvent>().is_some() {
+            // Keep the latency readout live while the page is open.
+            let latency = ctx.state().get_mut::<GameSession>()
+                .filter(|session| session.latency.version() != self.3)
+                .map(|session| (session.latency.version(), session.latency.summary()));
+            if let Some((version, summary)) = latency {
+                self.3 = version;
+                if let Some(item) = self.1.content().find_at::<DataItem>(10) {
+                    *item.label() = summary.latency_label();
+                }
+            }
+        } else if event.downcast_ref::<ResetLatencyStats>().is_some() {
+            if let Some(session) = ctx.state().get_mut::<GameSession>() {
+                session.latency.reset();
+            }
+        } else if event.downcast_ref::<UseLastSeed>().is_some() {
+            let seed = ctx.state().get_mut::<GameSession>().unwrap().sim.seed;
+            ctx.state().get_mut::<GameSettings>().unwrap().set_seed(Some(seed));
+            *self.1.content().find_at::<DataItem>(11).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().seed_label();
+        } else if event.downcast_ref::<UseRandomSeed>().is_some() {
+            ctx.state().get_mut::<GameSettings>().unwrap().set_seed(None);
+            *self.1.content().find_at::<DataItem>(11).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().seed_label();
+        } else if event.downcast_ref::<ToggleFliesDive>().is_some() {
+            let can_fly = !ctx.state().get_mut::<GameSettings>().unwrap().can_fly;
+            ctx.state().get_mut::<GameSettings>().unwrap().can_fly = can_fly;
+            let val = if can_fly {"Yes"} else {"No"};
+            *self.1.content().find_at::<DataItem>(13).unwrap().label() = format!("Enemy Dive Attacks: {}", val);
+            let buttons = &mut self.1.content().find_at::<DataItem>(13).unwrap().buttons();
+            let label = &mut buttons.as_mut().unwrap()[0].label().as_mut().unwrap().text().spans[0].text;
+            *label = if can_fly { "Turn Off".to_string() } else { "Turn On".to_string() };
+        } else if event.downcast_ref::<CyclePowerUpDrops>().is_some() {
+            ctx.state().get_mut::<GameSettings>().unwrap().cycle_power_up_drop_chance();
+            *self.1.content().find_at::<DataItem>(14).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().power_up_label();
+        } else if event.downcast_ref::<CycleBossWaves>().is_some() {
+            ctx.state().get_mut::<GameSettings>().unwrap().cycle_boss_every();
+            *self.1.content().find_at::<DataItem>(15).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().boss_label();
+        } else if event.downcast_ref::<CycleChallengeStages>().is_some() {
+            ctx.state().get_mut::<GameSettings>().unwrap().cycle_challenge_every();
+            *self.1.content().find_at::<DataItem>(16).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().challenge_label();
+        } else if event.downcast_ref::<CycleEndlessTargets>().is_some() {
+            ctx.state().get_mut::<GameSettings>().unwrap().cycle_endless_targets();
+            *self.1.content().find_at::<DataItem>(17).unwrap().label() = ctx.state().get_mut::<GameSettings>().unwrap().endless_label();
+        } else if event.downcast_ref::<NextWaveSet>().is_some() {
+            let current = ctx.state().get_mut::<GameSettings>().unwrap().wave_set.clone();
+            let names: Vec<String> = ctx.state().get_mut::<GameSession>().unwrap().wave_sets.iter().map(|s| s.name.clone()).collect();
+            let index = names.iter().position(|n| Some(n) == current.as_ref()).unwrap_or(0);
+            if let Some(next) = names.get((index + 1) % names.len().max(1)) {
+                ctx.state().get_mut::<GameSettings>().unwrap().set_wave_set(next);
+                *self.1.content().find_at::<DataItem>(12).unwrap().label() = format!("Wave Set: {}", next);
+            }
         }
         true
     }
@@ -105,9 +162,20 @@ impl OnEvent for Settings {
 
 impl AppPage for Settings {
     fn has_nav(&self) -> bool {false}
-    fn navigate(self: Box<Self>, ctx: &mut Context, index: usize) -> Result<Box<dyn AppPage>, Box<dyn AppPage>> {
+    fn navigate(mut self: Box<Self>, ctx: &mut Context, index: usize) -> Result<Box<dyn AppPage>, Box<dyn AppPage>> {
         match index {
-            0 => Ok(Box::new(Galaga::new(ctx))),
+            0 => match self.2.take() {
+                Some(game) => {
+                    // Play picks up again with a countdown if Settings paused it.
+                    if let Some(session) = ctx.state().get_mut::<GameSession>() {
+                        if matches!(self.4, GamePhase::Playing | GamePhase::Countdown { .. }) && session.phase == GamePhase::Paused {
+                            Galaga::set_phase(session, GamePhase::Countdown { started: Instant::now() });
+                        }
+                    }
+                    Ok(game)
+                }
+                None => Ok(Box::new(Galaga::new(ctx))),
+            },
             _ => Err(self)
         }
     }
@@ -115,12 +183,30 @@ impl AppPage for Settings {
 
 
 impl Settings {
-    pub fn new(ctx: &mut Context) -> Self {
+    pub fn new(ctx: &mut Context, game: Box<Galaga>, resume: GamePhase) -> Self {
         let pressure = format!("Touchpad Pressure: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().peak_min);
         let can_shoot = format!("Enemy Flies Can Shoot: {}", if ctx.state().get_mut::<GameSettings>().unwrap().can_shoot {"Yes"} else {"No"});
         let auto_move = format!("Player Auto Moves: {}", if ctx.state().get_mut::<GameSettings>().unwrap().player_auto_move {"Yes"} else {"No"});
         let auto_shoot = format!("Player Auto Shoots: {}", if ctx.state().get_mut::<GameSettings>().unwrap().player_auto_shoot {"Yes"} else {"No"});
         let invincible = format!("Player Is Invincible: {}", if ctx.state().get_mut::<GameSettings>().unwrap().player_invincible {"Yes"} else {"No"});
+        let scanning = format!("Single-Switch Scanning: {}", if ctx.state().get_mut::<GameSettings>().unwrap().scanning_mode {"Yes"} else {"No"});
+        let scan_interval = format!("Scan Interval: {:.2}s", ctx.state().get_mut::<GameSettings>().unwrap().scan_interval);
+        let graded_bands = format!("Pressure Bands: {}", if ctx.state().get_mut::<GameSettings>().unwrap().graded_bands {"Yes"} else {"No"});
+        let move_band = format!("Move Band Starts At: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().band_move_min);
+        let shoot_band = format!("Shoot Band Starts At: {:.0}", ctx.state().get_mut::<GameSettings>().unwrap().band_shoot_min);
+        let latency_version = ctx.state().get_mut::<GameSession>().unwrap().latency.version();
+        let latency = ctx.state().get_mut::<GameSession>().unwrap().latency.summary();
+        let seed = ctx.state().get_mut::<GameSettings>().unwrap().seed_label();
+        let wave_set = ctx.state().get_mut::<GameSettings>().unwrap().wave_set.clone();
+        let wave_set = ctx.state().get_mut::<GameSession>().unwrap().wave_set(wave_set.as_deref()).map(|s| s.name.clone()).unwrap_or_default();
+        let wave_set = format!("Wave Set: {}", wave_set);
+        let can_fly = ctx.state().get_mut::<GameSettings>().unwrap().can_fly;
+        let dives = format!("Enemy Dive Attacks: {}", if can_fly {"Yes"} else {"No"});
+        let power_ups = ctx.state().get_mut::<GameSettings>().unwrap().power_up_label();
+        let bosses = ctx.state().get_mut::<GameSettings>().unwrap().boss_label();
+        let challenges = ctx.state().get_mut::<GameSettings>().unwrap().challenge_label();
+        let endless = ctx.state().get_mut::<GameSettings>().unwrap().endless_label();
+        let server_info = ctx.state().get_mut::<ServerInfo>().cloned();
 
         let buttons = vec![
             SettingsButton::new(ctx, &pressure, "Increase or decrease pressure required to perform an action.", vec![
@@ -139,31 +225,117 @@ impl Settings {
             SettingsButton::new(ctx, &invincible, "Allows player to be invincible to enemy fire.", vec![
                 ("add", "Turn On", Box::new(|ctx: &mut Context| ctx.trigger_event(ToggleInvincibility)) as Box<dyn FnMut(&mut Context)>)
             ]),
+            SettingsButton::new(ctx, &scanning, "Highlights left, right and shoot in turn; any press performs the highlighted action.", vec![
+                ("add", "Turn On", Box::new(|ctx: &mut Context| ctx.trigger_event(ToggleScanningMode)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &scan_interval, "Increase or decrease how long each action stays highlighted.", vec![
+                ("add", "Decrease", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustScanIntervalEvent(-0.25))) as Box<dyn FnMut(&mut Context)>),
+                ("add", "Increase", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustScanIntervalEvent(0.25))) as Box<dyn FnMut(&mut Context)>),
+            ]),
+            SettingsButton::new(ctx, &graded_bands, "Lets the strength of a single press choose between moving and shooting.", vec![
+                ("add", "Turn On", Box::new(|ctx: &mut Context| ctx.trigger_event(ToggleGradedBands)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &move_band, "Presses from here up to the shoot band move the player.", vec![
+                ("add", "Decrease", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustMoveBandEvent(-50.0))) as Box<dyn FnMut(&mut Context)>),
+                ("add", "Increase", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustMoveBandEvent(50.0))) as Box<dyn FnMut(&mut Context)>),
+            ]),
+            SettingsButton::new(ctx, &shoot_band, "Presses at or above this pressure shoot.", vec![
+                ("add", "Decrease", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustShootBandEvent(-50.0))) as Box<dyn FnMut(&mut Context)>),
+                ("add", "Increase", Box::new(|ctx: &mut Context| ctx.trigger_event(AdjustShootBandEvent(50.0))) as Box<dyn FnMut(&mut Context)>),
+            ]),
+            SettingsButton::new(ctx, &latency.latency_label(), &latency.sequence_label(), vec![
+                ("add", "Reset", Box::new(|ctx: &mut Context| ctx.trigger_event(ResetLatencyStats)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &seed, "Replays the last run's enemy behaviour on every new run, or picks a new seed each time.", vec![
+                ("add", "Use Last Run", Box::new(|ctx: &mut Context| ctx.trigger_event(UseLastSeed)) as Box<dyn FnMut(&mut Context)>),
+                ("add", "Random", Box::new(|ctx: &mut Context| ctx.trigger_event(UseRandomSeed)) as Box<dyn FnMut(&mut Context)>),
+            ]),
+            SettingsButton::new(ctx, &wave_set, "Chooses the waves played from the next run. Extra sets are read from the waves folder.", vec![
+                ("add", "Next Set", Box::new(|ctx: &mut Context| ctx.trigger_event(NextWaveSet)) as Box<dyn FnMut(&mut Context)>),
+            ]),
+            SettingsButton::new(ctx, &dives, "Lets enemies leave the formation and swoop down at the player.", vec![
+                ("add", if can_fly {"Turn Off"} else {"Turn On"}, Box::new(|ctx: &mut Context| ctx.trigger_event(ToggleFliesDive)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &power_ups, "How often destroyed enemies drop a power-up for the player to catch.", vec![
+                ("add", "Change", Box::new(|ctx: &mut Context| ctx.trigger_event(CyclePowerUpDrops)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &bosses, "Replaces every few waves with one large boss, from the next run.", vec![
+                ("add", "Change", Box::new(|ctx: &mut Context| ctx.trigger_event(CycleBossWaves)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &challenges, "Every few waves, enemies fly past without shooting for bonus points, from the next run.", vec![
+                ("add", "Change", Box::new(|ctx: &mut Context| ctx.trigger_event(CycleChallengeStages)) as Box<dyn FnMut(&mut Context)>)
+            ]),
+            SettingsButton::new(ctx, &endless, "Caps how many enemies the endless waves grow to, from the next run.", vec![
+                ("add", "Change", Box::new(|ctx: &mut Context| ctx.trigger_event(CycleEndlessTargets)) as Box<dyn FnMut(&mut Context)>)
+            ]),
         ];
 
+        let mut buttons = buttons;
+        if let Some(info) = server_info {
+            let fingerprint = match &info.fingerprint {
+                Some(fingerprint) => format!("Certificate fingerprint (SHA-256): {}", fingerprint),
+                None => "Unencrypted connection, for development only.".to_string(),
+            };
+            buttons.push(Box::new(DataItem::new(ctx, None, &format!("Controller Server: {}", info.url), None, Some(&fingerprint), None, None)));
+        }
+
         let back = IconButton::navigation(ctx, "left", |ctx: &mut Context| ctx.trigger_event(NavigateEvent(0)));
 
         let header = Header::stack(ctx, Some(back), "Settings", None);
         let content = Content::new(Offset::Start, buttons);
 
-        Settings(Stack::default(), Page::new(Some(header), content, None))
+        Settings(Stack::default(), Page::new(Some(header), content, None), Some(game), latency_version, resume)
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct GameControls(Row, Button, Button);
+impl OnEvent for GameControls {}
+
+impl GameControls {
+    pub fn new(ctx: &mut Context) -> Self {
+        let primary = Button::secondary(ctx, Some("add"), GamePhase::Title.primary_label(), None, |ctx: &mut Context| ctx.trigger_event(GameCommandEvent(GameCommand::Primary)));
+        let stop = Button::secondary(ctx, Some("add"), "End Game", None, |ctx: &mut Context| ctx.trigger_event(GameCommandEvent(GameCommand::Stop)));
+        GameControls(Row::center(16.0), primary, stop)
+    }
+
+    fn set_labels(&mut self, phase: GamePhase) {
+        if let Some(text) = self.1.label().as_mut() {
+            text.text().spans[0].text = phase.primary_label().to_string();
+        }
+        if let Some(text) = self.2.label().as_mut() {
+            text.text().spans[0].text = phase.stop_label().to_string();
+        }
     }
 }
 
 #[derive(Debug, Component)]
-pub struct Galaga(Column, Header, Text, Gameboard);
+pub struct Galaga(Column, Header, Text, Gameboard, Text, GameControls);
 impl OnEvent for Galaga {
     fn on_event(&mut self, ctx: &mut Context, event: &mut dyn Event) -> bool {
-        self.2.text().spans[0].text = format!("Score: {}", unsafe { SCORE });
+        let Some((score, phase, banner)) = ctx.state().get_mut::<GameSession>().map(|s| (s.sim.score, s.phase, Self::banner_text(s))) else { return true };
+        self.2.text().spans[0].text = format!("Score: {}", score);
+        self.4.text().spans[0].text = banner;
+        self.5.set_labels(phase);
         true
     }
 }
 
 impl AppPage for Galaga {
     fn navigate(self: Box<Self>, ctx: &mut Context, index: usize) -> Result<Box<dyn AppPage>, Box<dyn AppPage>> {
-        println!("NAVIGATING TO PAGE {}", index);
         match index {
-            0 => Ok(Box::new(Settings::new(ctx))),
+            0 => {
+                let mut resume = GamePhase::Title;
+                if let Some(session) = ctx.state().get_mut::<GameSession>() {
+                    resume = session.phase;
+                    let next = match resume {
+                        GamePhase::Playing | GamePhase::Countdown { .. } => GamePhase::Paused,
+                        phase => phase,
+                    };
+                    Self::set_phase(session, next);
+                }
+                Ok(Box::new(Settings::new(ctx, self, resume)))
+            }
             _ => Err(self)
         }
     }
@@ -173,6 +345,13 @@ impl AppPage for Galaga {
 impl Galaga {
     pub fn new(ctx: &mut Context) -> Self {
         if ctx.state().get_mut::<GameSettings>().is_none() {Self::initialize_game_state(ctx);}
+
+        // A fresh board has nothing drawn on it, so any session already in
+        // state has to start over to match.
+        if let Some(session) = ctx.state().get_mut::<GameSession>() {
+            session.reset();
+        }
+
         let gameboard = Self::create_gameboard(ctx);
 
         let settings = IconButton::navigation(ctx, "settings", |ctx: &mut Context| ctx.trigger_event(NavigateEvent(0)));
@@ -180,340 +359,251 @@ impl Galaga {
         let header = Header::stack(ctx, None, "Galaga", Some(settings));
         let text_size = ctx.theme.fonts.size.h4;
         let text = Text::new(ctx, "Score: 0", TextStyle::Heading, text_size, Align::Center);
-        Galaga(Column::center(24.0), header, text, gameboard)
+        let banner_size = ctx.theme.fonts.size.h5;
+        let banner = Text::new(ctx, "", TextStyle::Heading, banner_size, Align::Center);
+        let controls = GameControls::new(ctx);
+        Galaga(Column::center(24.0), header, text, gameboard, banner, controls)
     }
 
     pub fn new_with_server(ctx: &mut Context, server: GameServer, event_handler: ServerEventHandler) -> Self {
         if ctx.state().get_mut::<GameSettings>().is_none() {Self::initialize_game_state(ctx);}
 
-        unsafe {
-            GAME_SERVER = Some(server);
-            SERVER_EVENT_HANDLER = Some(event_handler);
+        ctx.state().set(server.info());
+
+        if let Some(session) = ctx.state().get_mut::<GameSession>() {
+            session.server = Some(server);
+            session.event_handler = Some(event_handler);
         }
 
         Self::new(ctx)
     }
 
     fn initialize_game_state(ctx: &mut Context) {
-        unsafe {
-            ENEMIES_CREATED = false;
-            EXPLOSIONS = Some(HashMap::new());
-            PLAYER_RESPAWN_TIME = None;
-            PLAYER_IS_DEAD = false;
-            SCORE = 0;
-            GAME_SETTINGS = Some(GameSettings::new());
-            GAME_OVER_TIME = None;
-            GAME_IS_OVER = false;
-        }
-
         ctx.state().set(GameSettings::new());
-
-        PlayerLives::initialize_with_lives(4);
-        EnemyManager::initialize();
-        PlayerManager::initialize();
+        ctx.state().set(GameSession::new());
     }
 
-    fn reset_game_state(ctx: &mut Context, board: &mut Gameboard) {
-        println!("RESETTING GAME STATE");
-
-        Self::clear_all_sprites(ctx, board);
-
-        unsafe {
-            ENEMIES_CREATED = false;
-            EXPLOSIONS = Some(HashMap::new());
-            PLAYER_RESPAWN_TIME = None;
-            PLAYER_IS_DEAD = false;
-            SCORE = 0;
-            GAME_OVER_TIME = None;
-            GAME_IS_OVER = false;
-        }
-
-        PlayerLives::initialize_with_lives(4);
-        EnemyManager::initialize();
-        PlayerManager::initialize();
+    fn reset_game_state(session: &mut GameSession, board: &mut Gameboard) {
+        session.renderer.clear(board);
+        Self::clear_all_sprites(board);
 
-        let player = PlayerManager::create_player(ctx);
-        board.insert_sprite(ctx, player);
-
-        Self::update_score_display(ctx, board);
-
-        println!("*** GAME RESET COMPLETE ***");
+        session.reset();
     }
 
-    fn clear_all_sprites(ctx: &mut Context, board: &mut Gameboard) {
-        let sprite_ids: Vec<String> = board.2.keys().cloned().collect();
-        for sprite_id in sprite_ids {
-            Self::remove_sprite_from_board(ctx, board, &sprite_id);
-        }
+    fn clear_all_sprites(board: &mut Gameboard) {
+        board.2.clear();
+        board.0.0.clear();
     }
 
     fn create_gameboard(ctx: &mut Context) -> Gameboard {
-        let mut gameboard = Gameboard::new(ctx, AspectRatio::FiveSeven, Box::new(Self::on_event));
-        let player = PlayerManager::create_player(ctx);
-        gameboard.insert_sprite(ctx, player);
-
-        Self::create_score_display(ctx, &mut gameboard);
-
-        gameboard
+        Gameboard::new(ctx, AspectRatio::FiveSeven, Box::new(Self::on_event))
     }
 
-    fn create_score_display(ctx: &mut Context, board: &mut Gameboard) {
-        unsafe {
-            let score = SCORE;
-            let lives_info = PlayerLives::get_display_info();
-            let wave = EnemyManager::get_wave_count();
+    fn handle_server_input(session: &mut GameSession, ctx: &mut Context, board: &mut Gameboard) {
+        let settings = ctx.state().get_mut::<GameSettings>().cloned().unwrap_or_default();
+
+        let Some(event_handler) = &session.event_handler else { return };
+        let actions = event_handler.process_events_for_game(&settings, &mut session.latency);
+
+        for (action, timing) in actions {
+            let is_charge = matches!(action, GameAction::ChargeStart | GameAction::ChargeRelease);
+            let is_phase = matches!(action, GameAction::Start | GameAction::TogglePause | GameAction::Stop);
+            if is_phase || !session.phase.is_playing() {
+                Self::handle_phase_action(session, ctx, board, action);
+            } else if settings.scanning_mode && !is_charge {
+                ScanningControl::handle_press(&mut session.scanning, &mut session.input);
+            } else {
+                Self::apply_server_action(session, ctx, board, action);
+            }
 
-            Self::create_life_sprites(ctx, board, lives_info.lives);
+            let tick = session.tick_count;
+            session.latency.record_applied(&timing, Instant::now(), tick);
+            timing.acknowledge(now_millis(), tick);
         }
     }
 
-    fn update_score_display(ctx: &mut Context, board: &mut Gameboard) {
-        unsafe {
-            let score = SCORE;
-            let lives_info = PlayerLives::get_display_info();
-            let wave = EnemyManager::get_wave_count();
-
-            Self::update_life_sprites(ctx, board, lives_info.lives);
-        }
+    fn is_switch_press(event: &KeyboardEvent) -> bool {
+        matches!(
+            event,
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Space) } |
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowUp) }
+        )
     }
 
-    fn create_life_sprites(ctx: &mut Context, board: &mut Gameboard, lives: u32) {
-        Self::remove_all_life_sprites(ctx, board);
-
-        for i in 0..lives {
-            let life_sprite_id = format!("life_{}", i);
-            let x_pos = LIFE_SPRITE_START_X + (i as f32 * LIFE_SPRITE_SPACING);
-
-            let mut life_sprite = Sprite::new(
-                ctx,
-                &life_sprite_id,
-                "spaceship",
-                LIFE_SPRITE_SIZE,
-                (Offset::Static(x_pos), Offset::Static(LIFE_SPRITE_Y))
-            );
-
-            life_sprite.adjustments().0 = 0.0;
-            life_sprite.adjustments().1 = 0.0;
-
-            board.insert_sprite(ctx, life_sprite);
-        }
+    fn is_menu_move(event: &KeyboardEvent) -> bool {
+        Self::is_switch_press(event) || matches!(
+            event,
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowDown) }
+        )
     }
 
-    fn update_life_sprites(ctx: &mut Context, board: &mut Gameboard, lives: u32) {
-        let current_life_sprites: Vec<String> = board.2.keys()
-            .filter(|id| id.starts_with("life_"))
-            .cloned()
-            .collect();
-
-        let current_count = current_life_sprites.len() as u32;
-
-        if current_count != lives {
-            Self::create_life_sprites(ctx, board, lives);
-        }
+    // Outside of play a controller can only start the game from the title
+    // screen (any press will do) or use the explicit start and pause messages.
+    fn handle_phase_action(session: &mut GameSession, ctx: &mut Context, board: &mut Gameboard, action: GameAction) {
+        let command = match action {
+            GameAction::Start => GameCommand::Start,
+            GameAction::TogglePause => GameCommand::TogglePause,
+            GameAction::Stop => GameCommand::Stop,
+            _ if session.phase == GamePhase::Title => GameCommand::Start,
+            _ => return,
+        };
+        Self::apply_command(session, ctx, board, command);
     }
 
-    fn remove_all_life_sprites(ctx: &mut Context, board: &mut Gameboard) {
-        let life_sprite_ids: Vec<String> = board.2.keys()
-            .filter(|id| id.starts_with("life_"))
-            .cloned()
-            .collect();
-
-        for life_id in life_sprite_ids {
-            Self::remove_sprite_from_board(ctx, board, &life_id);
-        }
-    }
-
-    fn add_score(ctx: &mut Context, board: &mut Gameboard, points: u32) {
-        unsafe {
-            if !GAME_IS_OVER {
-                SCORE += points;
-                let score = SCORE;
-                println!("Score: {}", score);
+    fn apply_command(session: &mut GameSession, ctx: &mut Context, board: &mut Gameboard, command: GameCommand) {
+        let (seed, wave_set, max_targets, boss_every, challenge_every) = ctx.state().get_mut::<GameSettings>()
+            .map(|s| (s.seed, s.wave_set.clone(), s.endless_targets, s.boss_every, s.challenge_every))
+            .unwrap_or_default();
+        let next = match (command, session.phase) {
+            (GameCommand::Primary | GameCommand::Start, GamePhase::Title) => {
+                session.start_run(seed, wave_set.as_deref(), max_targets, boss_every, challenge_every);
+                GamePhase::Countdown { started: Instant::now() }
             }
-        }
-        Self::update_score_display(ctx, board);
+            (GameCommand::Primary | GameCommand::Start, GamePhase::Results) => {
+                Self::reset_game_state(session, board);
+                session.start_run(seed, wave_set.as_deref(), max_targets, boss_every, challenge_every);
+                GamePhase::Countdown { started: Instant::now() }
+            }
+            (GameCommand::Primary, GamePhase::GameOver { .. }) => GamePhase::Results,
+            (GameCommand::Primary | GameCommand::TogglePause, GamePhase::Countdown { .. } | GamePhase::Playing) => GamePhase::Paused,
+            (GameCommand::Primary | GameCommand::Start | GameCommand::TogglePause, GamePhase::Paused) => GamePhase::Countdown { started: Instant::now() },
+            (GameCommand::Stop, GamePhase::Countdown { .. } | GamePhase::Playing | GamePhase::Paused) => GamePhase::Results,
+            (GameCommand::Stop, GamePhase::Results) => {
+                Self::reset_game_state(session, board);
+                GamePhase::Title
+            }
+            _ => return,
+        };
+        Self::set_phase(session, next);
     }
 
-    fn lose_life(ctx: &mut Context, board: &mut Gameboard) {
-        PlayerLives::handle_player_death();
-        let lives_info = PlayerLives::get_display_info();
-        println!("PLAYER died! Lives remaining: {}", lives_info.lives);
-
-        if PlayerLives::is_game_over() {
-            unsafe {
-                GAME_IS_OVER = true;
-                GAME_OVER_TIME = Some(Instant::now());
-            }
-            println!("GAME OVER! Final Score: {}", unsafe { SCORE });
-            Self::remove_all_life_sprites(ctx, board);
-        } else {
-            Self::update_score_display(ctx, board);
+    // Play only runs in Playing, every other phase holds the session's timers
+    // so nothing moves on behind an overlay.
+    fn set_phase(session: &mut GameSession, phase: GamePhase) {
+        match phase {
+            GamePhase::Playing => session.resume(),
+            _ => session.pause(),
+        }
+        if phase == GamePhase::Paused && session.phase != GamePhase::Paused {
+            session.pause_item = PauseItem::default();
         }
+        session.phase = phase;
     }
 
-    fn respawn_player(ctx: &mut Context, board: &mut Gameboard) {
-        unsafe {
-            let can_respawn = !PlayerLives::is_game_over() && !GAME_IS_OVER;
-
-            if can_respawn {
-                let player = PlayerManager::create_player(ctx);
-                board.insert_sprite(ctx, player);
-                println!("*** PLAYER RESPAWNED! ***");
+    fn banner_text(session: &GameSession) -> String {
+        match session.phase {
+            GamePhase::Title => "Press Start, Enter or your switch when ready".to_string(),
+            GamePhase::Countdown { .. } => format!("Get ready... {}", session.phase.countdown_remaining().unwrap_or(1)),
+            GamePhase::Playing => String::new(),
+            GamePhase::Paused => "Paused".to_string(),
+            GamePhase::GameOver { .. } => "Game Over".to_string(),
+            GamePhase::Results => {
+                let seconds = session.play_time.as_secs();
+                let text = format!(
+                    "Final score {} - Wave {} - {} enemies - {}:{:02} - Seed {}",
+                    session.sim.score,
+                    EnemyManager::get_wave_count(&session.sim.enemies),
+                    session.sim.enemies_destroyed,
+                    seconds / 60,
+                    seconds % 60,
+                    session.sim.seed,
+                );
+                match EnemyManager::challenge_results(&session.sim.enemies) {
+                    (_, 0) => text,
+                    (hits, total) => format!("{} - Challenge {}/{}", text, hits, total),
+                }
             }
-
-            PLAYER_IS_DEAD = false;
-            PLAYER_RESPAWN_TIME = None;
         }
     }
 
-    fn remove_sprite_from_board(ctx: &mut Context, board: &mut Gameboard, sprite_id: &str) {
-        if board.2.remove(sprite_id).is_some() {
-            board.0.0.remove(sprite_id);
-
-            if EnemyManager::is_enemy(sprite_id) {
-                EnemyManager::remove_enemy_from_base_positions(sprite_id);
-            }
+    fn phase_command(session: &GameSession, event: &KeyboardEvent) -> Option<GameCommand> {
+        match event {
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Escape) } if session.phase == GamePhase::Results => Some(GameCommand::Stop),
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Escape) } => Some(GameCommand::TogglePause),
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Enter) } if session.phase == GamePhase::Paused => Some(session.pause_item.command()),
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Enter) } if !session.phase.is_playing() => Some(GameCommand::Primary),
+            _ if session.phase == GamePhase::Title && Self::is_switch_press(event) => Some(GameCommand::Start),
+            _ => None,
         }
     }
 
-    fn is_life_sprite(sprite_id: &str) -> bool {
-        sprite_id.starts_with("life_")
+    fn apply_server_action(session: &mut GameSession, ctx: &mut Context, board: &mut Gameboard, action: GameAction) {
+        let player_action = match action {
+            GameAction::MoveRight => PlayerAction::MoveRight,
+            GameAction::MoveLeft => PlayerAction::MoveLeft,
+            GameAction::Patrol => PlayerAction::Patrol,
+            GameAction::Shoot => PlayerAction::Shoot,
+            GameAction::ChargeStart => PlayerAction::ChargeStart,
+            GameAction::ChargeRelease => PlayerAction::ChargeRelease,
+            GameAction::Start | GameAction::TogglePause | GameAction::Stop => return Self::handle_phase_action(session, ctx, board, action),
+        };
+        session.input.actions.push(player_action);
     }
 
-    fn handle_server_input(ctx: &mut Context, board: &mut Gameboard) {
-        unsafe {
-            if GAME_IS_OVER {
-                return;
+    fn handle_keyboard_input(input: &mut SimInput, event: &KeyboardEvent) {
+        match event {
+            KeyboardEvent { state, key: Key::Named(NamedKey::ArrowLeft) } => {
+                input.left = *state == KeyboardState::Pressed;
             }
-
-            if let Some(ref event_handler) = SERVER_EVENT_HANDLER {
-                if let Some(action) = event_handler.process_events_for_game(ctx) {
-                    match action {
-                        GameAction::MoveRight => {
-                            println!("Server input: Move Right");
-                            PlayerManager::handle_server_move_right(ctx, board);
-                        }
-                        GameAction::MoveLeft => {
-                            println!("Server input: Move Left");
-                            PlayerManager::handle_server_move_left(ctx, board);
-                        }
-                        GameAction::Shoot => {
-                            println!("Server input: Shoot");
-                            PlayerManager::handle_server_shoot(ctx, board);
-                        }
-                    }
-                }
+            KeyboardEvent { state, key: Key::Named(NamedKey::ArrowRight) } => {
+                input.right = *state == KeyboardState::Pressed;
             }
-        }
-    }
-
-    pub fn update_game_settings<F>(updater: F)
-    where
-        F: FnOnce(&mut GameSettings),
-    {
-        unsafe {
-            let settings_ptr = std::ptr::addr_of_mut!(GAME_SETTINGS);
-            if let Some(settings) = &mut *settings_ptr {
-                updater(settings);
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowUp) } => {
+                input.actions.push(PlayerAction::Shoot);
             }
+            KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::Space) } => {
+                input.actions.push(PlayerAction::ChargeStart);
+            }
+            KeyboardEvent { state: KeyboardState::Released, key: Key::Named(NamedKey::Space) } => {
+                input.actions.push(PlayerAction::ChargeRelease);
+            }
+            _ => {}
         }
     }
 
     fn on_event(board: &mut Gameboard, ctx: &mut Context, event: &mut dyn Event) -> bool {
-        if let Some(TickEvent) = event.downcast_ref::<TickEvent>() {
-            unsafe {
-                if GAME_IS_OVER {
-                    if let Some(game_over_time) = GAME_OVER_TIME {
-                        if Instant::now().duration_since(game_over_time) >= GAME_OVER_RESET_DELAY {
-                            Self::reset_game_state(ctx, board);
-                            return true;
-                        }
-                    }
-                    return true;
-                }
-            }
+        GameSession::with(ctx, |session, ctx| Self::on_session_event(session, board, ctx, event)).unwrap_or(true)
+    }
 
-            Self::handle_server_input(ctx, board);
+    fn on_session_event(session: &mut GameSession, board: &mut Gameboard, ctx: &mut Context, event: &mut dyn Event) -> bool {
+        if let Some(TickEvent) = event.downcast_ref::<TickEvent>() {
+            session.tick_count += 1;
+            session.sim.set_field(board.0.size(ctx));
 
-            unsafe {
-                if !ENEMIES_CREATED {
-                    EnemyManager::create_enemies(ctx, board);
-                    ENEMIES_CREATED = true;
-                }
-            }
+            Self::handle_server_input(session, ctx, board);
 
-            PlayerLives::update(ctx, board);
+            match session.phase {
+                GamePhase::Countdown { started } => {
+                    session.sim.ensure_enemies();
 
-            unsafe {
-                if let Some(respawn_time) = PLAYER_RESPAWN_TIME {
-                    if Instant::now().duration_since(respawn_time) >= RESPAWN_DELAY {
-                        Self::respawn_player(ctx, board);
+                    if Instant::now().duration_since(started) >= COUNTDOWN_DURATION {
+                        Self::set_phase(session, GamePhase::Playing);
                     }
                 }
-            }
-
-            unsafe {
-                if !PLAYER_IS_DEAD && !GAME_IS_OVER {
-                    PlayerManager::update_player_movement(ctx, board);
-                }
-            }
-
-            EnemyManager::update_enemy_pulse(ctx, board);
-            EnemyManager::update_enemy_shooting(ctx, board);
-
-            let enemy_bullets_to_remove = EnemyManager::update_enemy_bullets(ctx, board);
-            for bullet_id in &enemy_bullets_to_remove {
-                Self::remove_sprite_from_board(ctx, board, bullet_id);
-            }
-
-            unsafe {
-                if !PLAYER_IS_DEAD && !GAME_IS_OVER {
-                    let settings_ptr = std::ptr::addr_of!(GAME_SETTINGS);
-                    let player_invincible = (*settings_ptr).as_ref().map(|s| s.player_invincible).unwrap_or(false);
-
-                    if !player_invincible {
-                        if let Some(ref mut explosions) = EXPLOSIONS {
-                            let (player_hit, player_hit_pos) = CollisionManager::handle_player_enemy_bullet_collisions(
-                                ctx, board, explosions
-                            );
-
-                            if player_hit {
-                                Self::remove_sprite_from_board(ctx, board, "player");
-                                CollisionManager::spawn_explosion(ctx, board, player_hit_pos, explosions);
-                                Self::lose_life(ctx, board);
-
-                                PLAYER_IS_DEAD = true;
-                                PLAYER_RESPAWN_TIME = Some(Instant::now() + EXPLOSION_DURATION);
-
-                                println!("PLAYER HIT!");
-                            }
+                GamePhase::Playing => {
+                    ScanningControl::update(&mut session.scanning, ctx, board);
+
+                    let settings = ctx.state().get_mut::<GameSettings>().cloned().unwrap_or_default();
+                    let steps = session.clock.advance(Instant::now());
+                    for _ in 0..steps {
+                        Self::update_playing(session, &settings, SIM_STEP);
+                        if !session.phase.is_playing() {
+                            break;
                         }
                     }
                 }
-            }
-
-            let bullets_to_remove = PlayerManager::update_bullets(ctx, board);
-            for bullet_id in &bullets_to_remove {
-                Self::remove_sprite_from_board(ctx, board, bullet_id);
-            }
-
-            unsafe {
-                if let Some(ref mut explosions) = EXPLOSIONS {
-                    CollisionManager::handle_bullet_bullet_collisions(ctx, board, explosions);
+                GamePhase::GameOver { since } => {
+                    session.sim.step_effects(SIM_STEP);
 
-                    let collisions_count = CollisionManager::handle_player_bullet_enemy_collisions(
-                        ctx, board, explosions
-                    );
-
-                    if collisions_count > 0 {
-                        Self::add_score(ctx, board, collisions_count * 100);
+                    if Instant::now().duration_since(since) >= GAME_OVER_DURATION {
+                        Self::set_phase(session, GamePhase::Results);
                     }
-
-                    CollisionManager::update_explosions(ctx, board, explosions);
                 }
+                GamePhase::Title | GamePhase::Paused | GamePhase::Results => {}
             }
 
-            EnemyManager::check_and_manage_enemy_state(ctx, board);
+            session.renderer.sync(&session.sim, ctx, board);
+            session.overlay.sync(session.phase, session.pause_item, session.sim.score, ctx, board);
 
             let sprite_ids: Vec<String> = board.2.keys().cloned().collect();
             for id in sprite_ids {
@@ -525,15 +615,34 @@ impl Galaga {
                     }
                 }
             }
+        } else if let Some(GameCommandEvent(command)) = event.downcast_ref::<GameCommandEvent>() {
+            Self::apply_command(session, ctx, board, *command);
         } else if let Some(keyboard_event) = event.downcast_ref::<KeyboardEvent>() {
-            unsafe {
-                if !PLAYER_IS_DEAD && !GAME_IS_OVER {
-                    PlayerManager::handle_keyboard_input(ctx, board, keyboard_event);
+            if let Some(command) = Self::phase_command(session, keyboard_event) {
+                Self::apply_command(session, ctx, board, command);
+            } else if session.phase == GamePhase::Paused && Self::is_menu_move(keyboard_event) {
+                session.pause_item = session.pause_item.toggled();
+            } else if !session.sim.is_player_dead() && session.phase.is_playing() {
+                if ScanningControl::is_enabled(ctx) {
+                    if Self::is_switch_press(keyboard_event) {
+                        ScanningControl::handle_press(&mut session.scanning, &mut session.input);
+                    }
+                } else {
+                    Self::handle_keyboard_input(&mut session.input, keyboard_event);
                 }
             }
         }
         true
     }
+
+    fn update_playing(session: &mut GameSession, settings: &GameSettings, dt: f32) {
+        let input = session.input.take();
+        session.sim.step(&input, settings, dt);
+
+        if session.sim.is_game_over() {
+            Self::set_phase(session, GamePhase::GameOver { since: Instant::now() });
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -574,4 +683,116 @@ impl Event for ToggleInvincibility {
     fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
         children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
     }
-}
\ No newline at end of file
+}
+
+#[derive(Clone, Debug)]
+pub struct ResetLatencyStats;
+impl Event for ResetLatencyStats {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ToggleScanningMode;
+impl Event for ToggleScanningMode {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdjustScanIntervalEvent(pub f32);
+impl Event for AdjustScanIntervalEvent {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ToggleGradedBands;
+impl Event for ToggleGradedBands {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdjustMoveBandEvent(pub f32);
+impl Event for AdjustMoveBandEvent {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdjustShootBandEvent(pub f32);
+impl Event for AdjustShootBandEvent {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UseLastSeed;
+impl Event for UseLastSeed {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UseRandomSeed;
+impl Event for UseRandomSeed {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NextWaveSet;
+impl Event for NextWaveSet {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ToggleFliesDive;
+impl Event for ToggleFliesDive {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CyclePowerUpDrops;
+impl Event for CyclePowerUpDrops {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleBossWaves;
+impl Event for CycleBossWaves {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleChallengeStages;
+impl Event for CycleChallengeStages {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleEndlessTargets;
+impl Event for CycleEndlessTargets {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::time::Instant;
+
+use crate::server::InputTiming;
+
+const LATENCY_WINDOW: usize = 120;
+// How many missing seqs are remembered so a late arrival can be taken back
+// off `lost`. Anything older is counted as lost for good.
+const MISSING_WINDOW: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct InputLatency {
+    network_ms: VecDeque<f32>,
+    queue_ms: VecDeque<f32>,
+    last_seq: Option<u64>,
+    received: u64,
+    applied: u64,
+    out_of_order: u64,
+    lost: u64,
+    missing: BTreeSet<u64>,
+    last_applied_tick: u64,
+    // Bumped on every change so a readout only redraws when there is
+    // something new to show.
+    version: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct LatencySummary {
+    pub avg_network_ms: Option<f32>,
+    pub max_network_ms: Option<f32>,
+    pub avg_queue_ms: Option<f32>,
+    pub max_queue_ms: Option<f32>,
+    pub received: u64,
+    pub applied: u64,
+    pub out_of_order: u64,
+    pub lost: u64,
+    pub last_applied_tick: u64,
+}
+
+impl InputLatency {
+    pub fn new() -> Self {
+        Self {
+            network_ms: VecDeque::with_capacity(LATENCY_WINDOW),
+            queue_ms: VecDeque::with_capacity(LATENCY_WINDOW),
+            last_seq: None,
+            received: 0,
+            applied: 0,
+            out_of_order: 0,
+            lost: 0,
+            missing: BTreeSet::new(),
+            last_applied_tick: 0,
+            version: 0,
+        }
+    }
+
+    // Called for every message that arrives, whether or not it passes peak_min,
+    // so gaps in the sequence only count packets that never reached us.
+    pub fn observe_arrival(&mut self, timing: &InputTiming) {
+        self.received += 1;
+        self.version += 1;
+
+        if let Some(sent_at) = timing.sent_at {
+            let network = timing.received_at as f32 - sent_at as f32;
+            Self::push_sample(&mut self.network_ms, network);
+        }
+
+        let Some(seq) = timing.seq else { return };
+
+        match self.last_seq {
+            None => self.last_seq = Some(seq),
+            Some(last) if seq == last + 1 => self.last_seq = Some(seq),
+            Some(last) if seq > last + 1 => {
+                self.lost += seq - last - 1;
+                self.missing.extend((last + 1..seq).rev().take(MISSING_WINDOW));
+                while self.missing.len() > MISSING_WINDOW {
+                    self.missing.pop_first();
+                }
+                self.last_seq = Some(seq);
+            }
+            Some(_) => {
+                self.out_of_order += 1;
+                // Only a seq that was counted as lost comes back off it, so
+                // duplicates don't hide real loss.
+                if self.missing.remove(&seq) {
+                    self.lost -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn record_applied(&mut self, timing: &InputTiming, applied_at: Instant, tick: u64) {
+        self.applied += 1;
+        self.last_applied_tick = tick;
+        self.version += 1;
+
+        let queue = applied_at.duration_since(timing.received_instant).as_secs_f32() * 1000.0;
+        Self::push_sample(&mut self.queue_ms, queue);
+    }
+
+    pub fn reset(&mut self) {
+        let version = self.version + 1;
+        *self = Self::new();
+        self.version = version;
+    }
+
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            avg_network_ms: Self::average(&self.network_ms),
+            max_network_ms: Self::maximum(&self.network_ms),
+            avg_queue_ms: Self::average(&self.queue_ms),
+            max_queue_ms: Self::maximum(&self.queue_ms),
+            received: self.received,
+            applied: self.applied,
+            out_of_order: self.out_of_order,
+            lost: self.lost,
+            last_applied_tick: self.last_applied_tick,
+        }
+    }
+
+    fn push_sample(samples: &mut VecDeque<f32>, value: f32) {
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(value);
+    }
+
+    fn average(samples: &VecDeque<f32>) -> Option<f32> {
+        if samples.is_empty() {
+            return None;
+        }
+        Some(samples.iter().sum::<f32>() / samples.len() as f32)
+    }
+
+    fn maximum(samples: &VecDeque<f32>) -> Option<f32> {
+        samples.iter().cloned().reduce(f32::max)
+    }
+}
+
+impl Default for InputLatency {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencySummary {
+    pub fn latency_label(&self) -> String {
+        match (self.avg_network_ms, self.avg_queue_ms) {
+            (None, None) => "Controller Latency: No Data".to_string(),
+            (network, queue) => format!(
+                "Controller Latency: {} network, {} game",
+                Self::format_ms(network, self.max_network_ms),
+                Self::format_ms(queue, self.max_queue_ms),
+            ),
+        }
+    }
+
+    pub fn sequence_label(&self) -> String {
+        format!(
+            "Received {}, applied {}, lost {}, out of order {} (last applied on tick {}).",
+            self.received, self.applied, self.lost, self.out_of_order, self.last_applied_tick
+        )
+    }
+
+    fn format_ms(avg: Option<f32>, max: Option<f32>) -> String {
+        match (avg, max) {
+            (Some(avg), Some(max)) => format!("{:.0}ms (max {:.0}ms)", avg, max),
+            _ => "--".to_string(),
+        }
+    }
+}
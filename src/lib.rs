@@ -16,8 +16,20 @@ mod game;
 mod server;
 mod collision;
 mod settings;
+mod latency;
+mod tls;
+mod user_data;
+mod session;
+mod phase;
+mod clock;
 mod player;
 mod fly;
+mod scanning;
+mod world;
+mod sim;
+mod render;
+mod overlay;
+mod scoring;
 
 use game::Galaga;
 use game::Settings;
@@ -43,11 +55,49 @@ impl Application for MyApp {
         let assets = &mut ctx.assets;
         ctx.theme.brand.illustrations.insert(assets, "spaceship");
         ctx.theme.brand.illustrations.insert(assets, "b2");
+        ctx.theme.brand.illustrations.insert(assets, "b2_damaged");
         ctx.theme.brand.illustrations.insert(assets, "tiki_fly");
         ctx.theme.brand.illustrations.insert(assets, "northrop");
         ctx.theme.brand.illustrations.insert(assets, "bullet_downward");
         ctx.theme.brand.illustrations.insert(assets, "bullet_blue");
         ctx.theme.brand.illustrations.insert(assets, "explosion");
+        ctx.theme.brand.illustrations.insert(assets, "hit_spark");
+        ctx.theme.brand.illustrations.insert(assets, "combo_x");
+        ctx.theme.brand.illustrations.insert(assets, "shield");
+        ctx.theme.brand.illustrations.insert(assets, "boss_bar_back");
+        ctx.theme.brand.illustrations.insert(assets, "boss_bar_fill");
+        ctx.theme.brand.illustrations.insert(assets, "tractor_beam");
+        ctx.theme.brand.illustrations.insert(assets, "spaceship_captured");
+        ctx.theme.brand.illustrations.insert(assets, "powerup_rapid");
+        ctx.theme.brand.illustrations.insert(assets, "powerup_spread");
+        ctx.theme.brand.illustrations.insert(assets, "powerup_shield");
+        ctx.theme.brand.illustrations.insert(assets, "powerup_slow");
+        ctx.theme.brand.illustrations.insert(assets, "powerup_life");
+        ctx.theme.brand.illustrations.insert(assets, "digit_0");
+        ctx.theme.brand.illustrations.insert(assets, "digit_1");
+        ctx.theme.brand.illustrations.insert(assets, "digit_2");
+        ctx.theme.brand.illustrations.insert(assets, "digit_3");
+        ctx.theme.brand.illustrations.insert(assets, "digit_4");
+        ctx.theme.brand.illustrations.insert(assets, "digit_5");
+        ctx.theme.brand.illustrations.insert(assets, "digit_6");
+        ctx.theme.brand.illustrations.insert(assets, "digit_7");
+        ctx.theme.brand.illustrations.insert(assets, "digit_8");
+        ctx.theme.brand.illustrations.insert(assets, "digit_9");
+        ctx.theme.brand.illustrations.insert(assets, "bullet_charged");
+        ctx.theme.brand.illustrations.insert(assets, "bullet_piercing");
+        ctx.theme.brand.illustrations.insert(assets, "charge_segment");
+        ctx.theme.brand.illustrations.insert(assets, "charge_segment_max");
+        ctx.theme.brand.illustrations.insert(assets, "scan_left");
+        ctx.theme.brand.illustrations.insert(assets, "scan_right");
+        ctx.theme.brand.illustrations.insert(assets, "scan_shoot");
+        ctx.theme.brand.illustrations.insert(assets, "scan_highlight");
+        ctx.theme.brand.illustrations.insert(assets, "phase_overlay");
+        ctx.theme.brand.illustrations.insert(assets, "overlay_title");
+        ctx.theme.brand.illustrations.insert(assets, "overlay_paused");
+        ctx.theme.brand.illustrations.insert(assets, "menu_resume");
+        ctx.theme.brand.illustrations.insert(assets, "menu_end_game");
+        ctx.theme.brand.illustrations.insert(assets, "overlay_game_over");
+        ctx.theme.brand.illustrations.insert(assets, "overlay_results");
 
 
 
@@ -0,0 +1,139 @@
+use pelican_ui::Context;
+use pelican_ui_std::Offset;
+use pelican_game_engine::{Sprite, Gameboard};
+
+use crate::phase::{GamePhase, PauseItem};
+
+const OVERLAY_PREFIX: &str = "overlay_";
+const BACKDROP_ID: &str = "overlay_backdrop";
+const HIGHLIGHT_ID: &str = "overlay_highlight";
+
+// Card images are drawn scaled up together so their lettering matches, one
+// step for every `CARD_SCALE_WIDTH` px of board.
+const CARD_SCALE_WIDTH: f32 = 200.0;
+const TITLE_CARD: (&str, (f32, f32)) = ("overlay_title", (134.0, 18.0));
+const PAUSED_CARD: (&str, (f32, f32)) = ("overlay_paused", (74.0, 18.0));
+const RESUME_ITEM: (&str, (f32, f32)) = ("menu_resume", (74.0, 18.0));
+const END_GAME_ITEM: (&str, (f32, f32)) = ("menu_end_game", (98.0, 18.0));
+const GAME_OVER_CARD: (&str, (f32, f32)) = ("overlay_game_over", (110.0, 18.0));
+const RESULTS_CARD: (&str, (f32, f32)) = ("overlay_results", (86.0, 18.0));
+
+const DIGIT_SIZE: (f32, f32) = (14.0, 22.0);
+const COUNTDOWN_DIGIT_SCALE: f32 = 3.0;
+const HIGHLIGHT_PADDING: f32 = 6.0;
+
+// What the overlay currently shows. Anything that changes the picture is in
+// here so it is only redrawn when it has to be.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OverlayView {
+    Title,
+    Countdown(u64),
+    Paused(PauseItem),
+    GameOver(u32),
+    Results(u32),
+}
+
+// Draws a screen over the board for every phase but Playing: the start
+// prompt, the countdown, the pause menu, the final score and the results.
+#[derive(Debug, Default)]
+pub struct PhaseOverlay {
+    shown: Option<(OverlayView, (f32, f32))>,
+}
+
+impl PhaseOverlay {
+    pub fn sync(&mut self, phase: GamePhase, pause_item: PauseItem, score: u32, ctx: &mut Context, board: &mut Gameboard) {
+        let view = match phase {
+            GamePhase::Title => Some(OverlayView::Title),
+            GamePhase::Countdown { .. } => Some(OverlayView::Countdown(phase.countdown_remaining().unwrap_or(1))),
+            GamePhase::Playing => None,
+            GamePhase::Paused => Some(OverlayView::Paused(pause_item)),
+            GamePhase::GameOver { .. } => Some(OverlayView::GameOver(score)),
+            GamePhase::Results => Some(OverlayView::Results(score)),
+        };
+        let field = board.0.size(ctx);
+        let wanted = view.map(|view| (view, field));
+        if self.shown == wanted {
+            return;
+        }
+
+        Self::clear(board);
+        self.shown = wanted;
+        let Some(view) = view else { return };
+
+        let size = field;
+        let backdrop = Sprite::new(ctx, BACKDROP_ID, "phase_overlay", size, (Offset::Static(0.0), Offset::Static(0.0)));
+        board.insert_sprite(ctx, backdrop);
+
+        let scale = (field.0 / CARD_SCALE_WIDTH).max(1.0);
+        let (w, h) = field;
+        match view {
+            OverlayView::Title => {
+                Self::card(ctx, board, TITLE_CARD, scale, (w / 2.0, h * 0.4));
+            }
+            OverlayView::Countdown(left) => {
+                Self::number(ctx, board, left, scale * COUNTDOWN_DIGIT_SCALE, (w / 2.0, h * 0.4));
+            }
+            OverlayView::Paused(selected) => {
+                Self::card(ctx, board, PAUSED_CARD, scale, (w / 2.0, h * 0.3));
+                let resume = Self::card(ctx, board, RESUME_ITEM, scale, (w / 2.0, h * 0.45));
+                let end_game = Self::card(ctx, board, END_GAME_ITEM, scale, (w / 2.0, h * 0.55));
+                let (position, size) = match selected {
+                    PauseItem::Resume => resume,
+                    PauseItem::EndGame => end_game,
+                };
+                let pad = HIGHLIGHT_PADDING * scale;
+                let highlight = Sprite::new(
+                    ctx,
+                    HIGHLIGHT_ID,
+                    "scan_highlight",
+                    (size.0 + pad * 2.0, size.1 + pad * 2.0),
+                    (Offset::Static(position.0 - pad), Offset::Static(position.1 - pad)),
+                );
+                board.insert_sprite(ctx, highlight);
+            }
+            OverlayView::GameOver(score) => {
+                Self::card(ctx, board, GAME_OVER_CARD, scale, (w / 2.0, h * 0.35));
+                Self::number(ctx, board, score as u64, scale, (w / 2.0, h * 0.5));
+            }
+            OverlayView::Results(score) => {
+                Self::card(ctx, board, RESULTS_CARD, scale, (w / 2.0, h * 0.35));
+                Self::number(ctx, board, score as u64, scale, (w / 2.0, h * 0.5));
+            }
+        }
+    }
+
+    pub fn clear(board: &mut Gameboard) {
+        let ids: Vec<String> = board.2.keys()
+            .filter(|id| id.starts_with(OVERLAY_PREFIX))
+            .cloned()
+            .collect();
+
+        for id in ids {
+            board.2.remove(&id);
+            board.0.0.remove(&id);
+        }
+    }
+
+    // Draws `card` centred on `center` and returns where it went.
+    fn card(ctx: &mut Context, board: &mut Gameboard, card: (&str, (f32, f32)), scale: f32, center: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+        let (image, (w, h)) = card;
+        let size = (w * scale, h * scale);
+        let position = (center.0 - size.0 / 2.0, center.1 - size.1 / 2.0);
+        let sprite = Sprite::new(ctx, &format!("{}card_{}", OVERLAY_PREFIX, image), image, size, (Offset::Static(position.0), Offset::Static(position.1)));
+        board.insert_sprite(ctx, sprite);
+        (position, size)
+    }
+
+    fn number(ctx: &mut Context, board: &mut Gameboard, value: u64, scale: f32, center: (f32, f32)) {
+        let text = value.to_string();
+        let digit = (DIGIT_SIZE.0 * scale, DIGIT_SIZE.1 * scale);
+        let x = center.0 - digit.0 * text.len() as f32 / 2.0;
+        let y = center.1 - digit.1 / 2.0;
+
+        for (i, c) in text.chars().enumerate() {
+            let id = format!("{}digit_{}", OVERLAY_PREFIX, i);
+            let sprite = Sprite::new(ctx, &id, &format!("digit_{}", c), digit, (Offset::Static(x + i as f32 * digit.0), Offset::Static(y)));
+            board.insert_sprite(ctx, sprite);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use pelican_ui::events::Event;
+use pelican_ui::Context;
+use std::time::{Duration, Instant};
+
+pub const COUNTDOWN_DURATION: Duration = Duration::from_secs(3);
+pub const GAME_OVER_DURATION: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum GamePhase {
+    #[default]
+    Title,
+    Countdown { started: Instant },
+    Playing,
+    Paused,
+    GameOver { since: Instant },
+    Results,
+}
+
+impl GamePhase {
+    pub fn is_playing(&self) -> bool {
+        matches!(self, GamePhase::Playing)
+    }
+
+    // Whole seconds left on the countdown, rounded up so it reads 3, 2, 1.
+    pub fn countdown_remaining(&self) -> Option<u64> {
+        match self {
+            GamePhase::Countdown { started } => {
+                let left = COUNTDOWN_DURATION.saturating_sub(Instant::now().duration_since(*started));
+                Some((left.as_millis() as u64).div_ceil(1000).max(1))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn shift(&mut self, by: Duration) {
+        match self {
+            GamePhase::Countdown { started } => *started += by,
+            GamePhase::GameOver { since } => *since += by,
+            _ => {}
+        }
+    }
+
+    pub fn primary_label(&self) -> &'static str {
+        match self {
+            GamePhase::Title => "Start",
+            GamePhase::Countdown { .. } | GamePhase::Playing => "Pause",
+            GamePhase::Paused => "Resume",
+            GamePhase::GameOver { .. } => "See Results",
+            GamePhase::Results => "Play Again",
+        }
+    }
+
+    pub fn stop_label(&self) -> &'static str {
+        match self {
+            GamePhase::Results => "Title Screen",
+            _ => "End Game",
+        }
+    }
+}
+
+// The two choices on the pause menu. Enter picks the highlighted one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PauseItem {
+    #[default]
+    Resume,
+    EndGame,
+}
+
+impl PauseItem {
+    pub fn toggled(self) -> Self {
+        match self {
+            PauseItem::Resume => PauseItem::EndGame,
+            PauseItem::EndGame => PauseItem::Resume,
+        }
+    }
+
+    pub fn command(self) -> GameCommand {
+        match self {
+            PauseItem::Resume => GameCommand::TogglePause,
+            PauseItem::EndGame => GameCommand::Stop,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameCommand {
+    // Whatever the big button currently says: start, pause, resume or replay.
+    Primary,
+    Start,
+    TogglePause,
+    Stop,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameCommandEvent(pub GameCommand);
+
+impl Event for GameCommandEvent {
+    fn pass(self: Box<Self>, _ctx: &mut Context, children: Vec<((f32, f32), (f32, f32))>) -> Vec<Option<Box<dyn Event>>> {
+        children.into_iter().map(|_| Some(self.clone() as Box<dyn Event>)).collect()
+    }
+}
@@ -0,0 +1,78 @@
+use crate::world::{Body, EntityKind, World};
+
+use super::{PlayerControl, PlayerManager, PlayerState, WING_ID};
+use super::manager::PLAYER_SIZE;
+
+const CAPTURE_PULL_DURATION: f32 = 1.5;
+const DOCK_DURATION: f32 = 1.2;
+
+pub struct PlayerCapture;
+
+impl PlayerCapture {
+    // A lone ship in play, the only kind a tractor beam can take and the
+    // only kind a freed ship can dock with.
+    pub fn is_single(player: &PlayerControl, world: &World) -> bool {
+        PlayerManager::has_control(player) && world.contains("player") && !PlayerManager::has_wing(world)
+    }
+
+    pub fn is_captured(player: &PlayerControl) -> bool {
+        matches!(player.state, PlayerState::Captured { .. })
+    }
+
+    pub fn capture(player: &mut PlayerControl, world: &World, time: f32) {
+        let Some(body) = world.get("player") else { return };
+        player.state = PlayerState::Captured { since: time, from: body.position };
+        player.charge_start = None;
+    }
+
+    // Starts a freed ship, currently at `from`, flying down to dock.
+    pub fn dock(player: &mut PlayerControl, world: &mut World, from: (f32, f32), time: f32) {
+        let size = world.get("player").map_or(PLAYER_SIZE, |body| body.size);
+        world.insert(WING_ID, Body::new(EntityKind::Player, "spaceship", from, size));
+        player.state = PlayerState::Docking { since: time, from };
+        player.charge_start = None;
+    }
+
+    // Moves the player up the beam toward `captor` (its position and size)
+    // or a freed ship toward its dock. Returns true once a pull is over and
+    // the ship is taken. If the captor is gone before then the player drops
+    // back to where they were.
+    pub fn update(player: &mut PlayerControl, world: &mut World, captor: Option<((f32, f32), (f32, f32))>, time: f32) -> bool {
+        match player.state {
+            PlayerState::Captured { since, from } => {
+                let Some((position, size)) = captor else {
+                    if let Some(body) = world.get_mut("player") {
+                        body.position = from;
+                    }
+                    player.state = PlayerState::Idle { last_shot: None };
+                    return false;
+                };
+
+                let t = ((time - since) / CAPTURE_PULL_DURATION).min(1.0);
+                if let Some(body) = world.get_mut("player") {
+                    let target = (position.0 + (size.0 - body.size.0) / 2.0, position.1 + size.1);
+                    body.position = Self::lerp(from, target, t);
+                }
+                t >= 1.0
+            }
+            PlayerState::Docking { since, from } => {
+                let t = ((time - since) / DOCK_DURATION).min(1.0);
+                let slot = PlayerManager::wing_slot(world);
+                match (world.get_mut(WING_ID), slot) {
+                    (Some(wing), Some(slot)) => wing.position = Self::lerp(from, slot, t),
+                    // Shot down on the way in.
+                    _ => player.state = PlayerState::Idle { last_shot: None },
+                }
+                if t >= 1.0 && PlayerManager::has_wing(world) {
+                    player.state = PlayerState::Idle { last_shot: None };
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn lerp(from: (f32, f32), to: (f32, f32), t: f32) -> (f32, f32) {
+        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+    }
+}
@@ -1,269 +1,294 @@
-use pelican_ui::{Context};
-use pelican_ui_std::Offset;
-use pelican_game_engine::{Sprite, Gameboard, SpriteAction};
-use pelican_ui::events::{KeyboardEvent, KeyboardState, Key, NamedKey};
-use std::time::Instant;
+use super::{PlayerState, PlayerControl, MovementDirection, ServerMovement};
+use super::{SHOOT_COOLDOWN, MOVEMENT_SPEED, SERVER_MOVEMENT_DURATION};
+use super::powerups::{PowerUps, RAPID_FIRE_FACTOR};
+use super::{CHARGE_BIG_SHOT, CHARGE_PIERCING_SHOT};
 
-use super::{PlayerState, MovementDirection, KeysHeld, ServerMovement};
-use super::{BULLET_SPEED, SHOOT_COOLDOWN, MOVEMENT_SPEED, SERVER_MOVEMENT_DURATION};
+use crate::world::{Body, EntityKind, World};
 
-static mut PLAYER_STATE: PlayerState = PlayerState::Idle { last_shot: None };
-static mut KEYS_HELD: KeysHeld = KeysHeld::new();
-static mut SERVER_MOVEMENT: Option<ServerMovement> = None;
+pub const PLAYER_SIZE: (f32, f32) = (50.0, 50.0);
+// The second ship of a dual fighter, docked on the right of the first.
+pub const WING_ID: &str = "player_wing";
+// Sideways speed of each spread shot bullet, as a fraction of its speed up.
+const SPREAD_DRIFT: [f32; 3] = [-0.2, 0.0, 0.2];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShotKind {
+    Normal,
+    Big,
+    Piercing,
+}
 
 pub struct PlayerManager;
 
 impl PlayerManager {
-    pub fn initialize() {
-        unsafe {
-            PLAYER_STATE = PlayerState::Idle { last_shot: None };
-            KEYS_HELD = KeysHeld::new();
-            SERVER_MOVEMENT = None;
-        }
+    pub fn initialize(player: &mut PlayerControl) {
+        *player = PlayerControl::new();
     }
 
-    pub fn create_player(ctx: &mut Context) -> Sprite {
-        let mut player = Sprite::new(ctx, "player", "spaceship", (50.0, 50.0), (Offset::Center, Offset::End));
-        player.adjustments().0 = 0.0;
-        player.adjustments().1 = 0.0;
-        player
+    pub fn create_player(world: &mut World) {
+        let (maxw, maxh) = world.field;
+        let position = ((maxw - PLAYER_SIZE.0) / 2.0, maxh - PLAYER_SIZE.1);
+        world.insert("player", Body::new(EntityKind::Player, "spaceship", position, PLAYER_SIZE));
     }
 
-    pub fn handle_keyboard_input(ctx: &mut Context, board: &mut Gameboard, event: &KeyboardEvent) -> bool {
-        unsafe {
-            match event {
-                KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowLeft) } => {
-                    KEYS_HELD.left = true;
-                    Self::update_player_state();
-                    true
-                }
-                KeyboardEvent { state: KeyboardState::Released, key: Key::Named(NamedKey::ArrowLeft) } => {
-                    KEYS_HELD.left = false;
-                    Self::update_player_state();
-                    true
-                }
-                KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowRight) } => {
-                    KEYS_HELD.right = true;
-                    Self::update_player_state();
-                    true
-                }
-                KeyboardEvent { state: KeyboardState::Released, key: Key::Named(NamedKey::ArrowRight) } => {
-                    KEYS_HELD.right = false;
-                    Self::update_player_state();
-                    true
-                }
-                KeyboardEvent { state: KeyboardState::Pressed, key: Key::Named(NamedKey::ArrowUp) } => {
-                    Self::handle_shooting(ctx, board);
-                    true
-                }
-                _ => false
-            }
-        }
+    pub fn set_keys_held(player: &mut PlayerControl, left: bool, right: bool, time: f32) {
+        player.keys_held.left = left;
+        player.keys_held.right = right;
+        Self::update_player_state(player, time);
     }
 
-    pub fn handle_server_move_right(ctx: &mut Context, board: &mut Gameboard) {
-        Self::set_server_movement(MovementDirection::Right);
-        println!("Server: Move Right activated");
-    }
-    
-    pub fn handle_server_move_left(ctx: &mut Context, board: &mut Gameboard) {
-        Self::set_server_movement(MovementDirection::Left);
-        println!("Server: Move Left activated");
+    pub fn handle_server_move_right(player: &mut PlayerControl, time: f32) {
+        Self::set_server_movement(player, MovementDirection::Right, time);
     }
-    
-    pub fn handle_server_shoot(ctx: &mut Context, board: &mut Gameboard) {
-        Self::handle_shooting(ctx, board);
-        println!("Server: Shoot activated");
+
+    pub fn handle_server_move_left(player: &mut PlayerControl, time: f32) {
+        Self::set_server_movement(player, MovementDirection::Left, time);
     }
 
-    pub fn handle_player_action(ctx: &mut Context, board: &mut Gameboard, action: SpriteAction) {
-        if matches!(action, SpriteAction::Shoot) && board.2.contains_key("player") {
-            Self::handle_shooting(ctx, board);
+    pub fn handle_server_patrol(player: &mut PlayerControl, world: &mut World, time: f32) {
+        let (maxw, _) = world.field;
+
+        if let Some(body) = world.get("player") {
+            let x = body.position.0;
+            let width = body.size.0 + world.get(WING_ID).map_or(0.0, |wing| wing.size.0);
+
+            player.patrol_direction = match player.patrol_direction {
+                MovementDirection::Right if x >= maxw - width - 5.0 => MovementDirection::Left,
+                MovementDirection::Left if x <= 5.0 => MovementDirection::Right,
+                direction => direction,
+            };
+            Self::set_server_movement(player, player.patrol_direction, time);
         }
     }
 
-    fn set_server_movement(direction: MovementDirection) {
-        unsafe {
-            SERVER_MOVEMENT = Some(ServerMovement {
-                direction,
-                start_time: Instant::now(),
-            });
+    pub fn handle_server_shoot(player: &mut PlayerControl, world: &mut World, time: f32, bullet_speed: f32) {
+        Self::handle_shooting(player, world, time, bullet_speed);
+    }
+
+    // Key repeat and pressure streams resend the start while held, so only
+    // the first one counts.
+    pub fn start_charge(player: &mut PlayerControl, time: f32) {
+        if player.charge_start.is_none() && Self::has_control(player) {
+            player.charge_start = Some(time);
         }
     }
 
-    fn can_shoot() -> bool {
-        unsafe {
-            let last_shot = match PLAYER_STATE {
-                PlayerState::Idle { last_shot } |
-                PlayerState::MovingLeft { last_shot, .. } |
-                PlayerState::MovingRight { last_shot, .. } |
-                PlayerState::MovingBoth { last_shot, .. } => last_shot,
-                PlayerState::Shooting { shot_time, .. } => Some(shot_time),
-                PlayerState::Destroyed => return false,
-            };
+    pub fn release_charge(player: &mut PlayerControl, world: &mut World, time: f32, bullet_speed: f32) {
+        let held = match player.charge_start.take() {
+            Some(start) => time - start,
+            None => return,
+        };
+
+        let kind = if held >= CHARGE_PIERCING_SHOT.as_secs_f32() {
+            ShotKind::Piercing
+        } else if held >= CHARGE_BIG_SHOT.as_secs_f32() {
+            ShotKind::Big
+        } else {
+            ShotKind::Normal
+        };
+
+        Self::handle_shooting_kind(player, world, time, bullet_speed, kind);
+    }
+
+    pub fn cancel_charge(player: &mut PlayerControl) {
+        player.charge_start = None;
+    }
 
-            last_shot.map_or(true, |t| Instant::now().duration_since(t) >= SHOOT_COOLDOWN)
+    pub fn charge_fraction(player: &PlayerControl, time: f32) -> Option<f32> {
+        player.charge_start.map(|start| {
+            ((time - start) / CHARGE_PIERCING_SHOT.as_secs_f32()).min(1.0)
+        })
+    }
+
+    fn set_server_movement(player: &mut PlayerControl, direction: MovementDirection, time: f32) {
+        player.server_movement = Some(ServerMovement {
+            direction,
+            start_time: time,
+        });
+    }
+
+    fn can_shoot(player: &PlayerControl, time: f32) -> bool {
+        let last_shot = match player.state {
+            PlayerState::Idle { last_shot } |
+            PlayerState::MovingLeft { last_shot, .. } |
+            PlayerState::MovingRight { last_shot, .. } |
+            PlayerState::MovingBoth { last_shot, .. } => last_shot,
+            PlayerState::Shooting { shot_time, .. } => Some(shot_time),
+            PlayerState::Destroyed | PlayerState::Captured { .. } | PlayerState::Docking { .. } => return false,
+        };
+
+        let mut cooldown = SHOOT_COOLDOWN.as_secs_f32();
+        if PowerUps::has_rapid_fire(player, time) {
+            cooldown /= RAPID_FIRE_FACTOR;
         }
+        last_shot.is_none_or(|t| time - t >= cooldown)
     }
 
-    fn handle_shooting(ctx: &mut Context, board: &mut Gameboard) {
-        if !Self::can_shoot() { return; }
+    fn handle_shooting(player: &mut PlayerControl, world: &mut World, time: f32, bullet_speed: f32) {
+        Self::handle_shooting_kind(player, world, time, bullet_speed, ShotKind::Normal);
+    }
 
-        let player_info = board.2.get_mut("player")
-            .map(|sprite| (sprite.position(ctx), *sprite.dimensions()));
+    fn handle_shooting_kind(player: &mut PlayerControl, world: &mut World, time: f32, bullet_speed: f32, kind: ShotKind) {
+        if !Self::has_control(player) { return; }
+        if kind == ShotKind::Normal && !Self::can_shoot(player, time) { return; }
 
-        if let Some((pos, size)) = player_info {
-            Self::shoot(ctx, board, pos, size);
+        // A dual fighter fires from both ships at once.
+        let ships: Vec<((f32, f32), (f32, f32))> = ["player", WING_ID].iter()
+            .filter_map(|id| world.get(id).map(|body| (body.position, body.size)))
+            .collect();
 
-            unsafe {
-                let direction = Self::get_current_direction();
-                PLAYER_STATE = PlayerState::Shooting {
-                    direction,
-                    shot_time: Instant::now()
-                };
+        if !ships.is_empty() {
+            for (pos, size) in ships {
+                if kind == ShotKind::Normal && PowerUps::has_spread_shot(player, time) {
+                    for drift in SPREAD_DRIFT {
+                        Self::shoot(world, pos, size, (drift * bullet_speed, -bullet_speed), kind);
+                    }
+                } else {
+                    Self::shoot(world, pos, size, (0.0, -bullet_speed), kind);
+                }
             }
+
+            let direction = Self::get_current_direction(player, time);
+            player.state = PlayerState::Shooting {
+                direction,
+                shot_time: time
+            };
         }
     }
 
-    fn shoot(ctx: &mut Context, board: &mut Gameboard, player_pos: (f32, f32), player_size: (f32, f32)) {
-        let b_size = (15.0, 15.0);
+    fn shoot(world: &mut World, player_pos: (f32, f32), player_size: (f32, f32), velocity: (f32, f32), kind: ShotKind) {
+        let (b_size, image, prefix) = match kind {
+            ShotKind::Normal => ((15.0, 15.0), "bullet_blue", "bullet_"),
+            ShotKind::Big => ((30.0, 30.0), "bullet_charged", "bullet_big_"),
+            ShotKind::Piercing => ((12.0, 36.0), "bullet_piercing", "bullet_pierce_"),
+        };
         let (x, y) = player_pos;
-        let bullet_id = format!("bullet_{}", uuid::Uuid::new_v4());
-        let bullet = Sprite::new(
-            ctx,
-            &bullet_id,
-            "bullet_blue",
-            b_size,
-            (
-                Offset::Static(x + ((player_size.0 - b_size.0) / 2.0)),
-                Offset::Static(y - 20.0),
-            ),
-        );
-        board.insert_sprite(ctx, bullet);
+        let bullet_id = world.next_id(prefix);
+        let position = (x + ((player_size.0 - b_size.0) / 2.0), y - b_size.1 - 5.0);
+        let bullet = Body::new(EntityKind::PlayerBullet(kind), image, position, b_size)
+            .with_owner("player")
+            .with_velocity(velocity);
+        world.insert(&bullet_id, bullet);
     }
 
-    fn get_current_direction() -> MovementDirection {
-        unsafe {
-            match Self::get_server_movement() {
-                MovementDirection::None => {
-                    let keys_copy = KEYS_HELD;
-                    keys_copy.to_direction()
-                },
-                server_dir => server_dir,
-            }
+    fn get_current_direction(player: &mut PlayerControl, time: f32) -> MovementDirection {
+        match Self::get_server_movement(player, time) {
+            MovementDirection::None => player.keys_held.to_direction(),
+            server_dir => server_dir,
         }
     }
 
-    fn get_server_movement() -> MovementDirection {
-        unsafe {
-            if let Some(server_mov) = SERVER_MOVEMENT {
-                if Instant::now().duration_since(server_mov.start_time) < SERVER_MOVEMENT_DURATION {
-                    return server_mov.direction;
-                } else {
-                    SERVER_MOVEMENT = None;
-                }
+    fn get_server_movement(player: &mut PlayerControl, time: f32) -> MovementDirection {
+        if let Some(server_mov) = player.server_movement {
+            if time - server_mov.start_time < SERVER_MOVEMENT_DURATION.as_secs_f32() {
+                return server_mov.direction;
+            } else {
+                player.server_movement = None;
             }
-            MovementDirection::None
         }
+        MovementDirection::None
     }
 
-    fn update_player_state() {
-        unsafe {
-            let direction = Self::get_current_direction();
-            
-            PLAYER_STATE = match (PLAYER_STATE, direction) {
-                (PlayerState::Destroyed, _) => PlayerState::Destroyed,
-                
-                (PlayerState::Shooting { shot_time, .. }, dir) => {
-                    Self::state_from_direction(dir, Some(shot_time))
-                }
-                
-                (state, dir) => {
-                    let last_shot = match state {
-                        PlayerState::Idle { last_shot } |
-                        PlayerState::MovingLeft { last_shot, .. } |
-                        PlayerState::MovingRight { last_shot, .. } |
-                        PlayerState::MovingBoth { last_shot, .. } => last_shot,
-                        _ => None,
-                    };
-                    Self::state_from_direction(dir, last_shot)
-                }
-            };
-        }
+    fn update_player_state(player: &mut PlayerControl, time: f32) {
+        let direction = Self::get_current_direction(player, time);
+
+        player.state = match (player.state, direction) {
+            (PlayerState::Destroyed, _) => PlayerState::Destroyed,
+            (state @ (PlayerState::Captured { .. } | PlayerState::Docking { .. }), _) => state,
+
+            (PlayerState::Shooting { shot_time, .. }, dir) => {
+                Self::state_from_direction(dir, Some(shot_time))
+            }
+
+            (state, dir) => {
+                let last_shot = match state {
+                    PlayerState::Idle { last_shot } |
+                    PlayerState::MovingLeft { last_shot, .. } |
+                    PlayerState::MovingRight { last_shot, .. } |
+                    PlayerState::MovingBoth { last_shot, .. } => last_shot,
+                    _ => None,
+                };
+                Self::state_from_direction(dir, last_shot)
+            }
+        };
     }
 
-    fn state_from_direction(direction: MovementDirection, last_shot: Option<Instant>) -> PlayerState {
+    fn state_from_direction(direction: MovementDirection, last_shot: Option<f32>) -> PlayerState {
         match direction {
             MovementDirection::None => PlayerState::Idle { last_shot },
             MovementDirection::Left => PlayerState::MovingLeft { last_shot, speed: MOVEMENT_SPEED },
             MovementDirection::Right => PlayerState::MovingRight { last_shot, speed: MOVEMENT_SPEED },
-            MovementDirection::Both => PlayerState::MovingBoth { 
-                last_shot, 
-                left_speed: MOVEMENT_SPEED, 
-                right_speed: MOVEMENT_SPEED 
+            MovementDirection::Both => PlayerState::MovingBoth {
+                last_shot,
+                left_speed: MOVEMENT_SPEED,
+                right_speed: MOVEMENT_SPEED
             },
         }
     }
 
-    pub fn update_bullets(ctx: &mut Context, board: &mut Gameboard) -> Vec<String> {
-        board.2.iter_mut()
-            .filter_map(|(id, sprite)| {
-                if id.starts_with("bullet_") {
-                    sprite.adjustments().1 -= BULLET_SPEED;
-                    if sprite.position(ctx).1 < -50.0 {
-                        Some(id.clone())
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            })
+    pub fn get_active_bullets(world: &World) -> Vec<(String, EntityKind, (f32, f32), (f32, f32))> {
+        world.of_kind(EntityKind::is_player_bullet)
+            .map(|(id, body)| (id.clone(), body.kind, body.position, body.size))
             .collect()
     }
 
-    pub fn get_active_bullets(board: &mut Gameboard, ctx: &mut Context) -> Vec<(String, (f32, f32), (f32, f32))> {
-        board.2.iter_mut()
-            .filter_map(|(id, sprite)| {
-                if id.starts_with("bullet_") {
-                    Some((id.clone(), sprite.position(ctx), *sprite.dimensions()))
-                } else {
-                    None
-                }
-            })
-            .collect()
+    pub fn destroy_player(player: &mut PlayerControl) {
+        player.state = PlayerState::Destroyed;
+        player.charge_start = None;
     }
 
-    pub fn get_player_state() -> PlayerState {
-        unsafe { PLAYER_STATE }
+    // Whether input moves and fires the ship. Not while captured or docking.
+    pub fn has_control(player: &PlayerControl) -> bool {
+        !matches!(player.state, PlayerState::Destroyed | PlayerState::Captured { .. } | PlayerState::Docking { .. })
     }
 
-    pub fn destroy_player() {
-        unsafe { PLAYER_STATE = PlayerState::Destroyed; }
+    pub fn is_player_destroyed(player: &PlayerControl) -> bool {
+        matches!(player.state, PlayerState::Destroyed)
     }
 
-    pub fn is_player_destroyed() -> bool {
-        unsafe { matches!(PLAYER_STATE, PlayerState::Destroyed) }
+    pub fn update_player_movement(player: &mut PlayerControl, world: &mut World, time: f32, dt: f32) {
+        if world.contains("player") {
+            Self::update_player_state(player, time);
+            crate::player::movement::handle_movement_by_state(world, player.state, dt);
+            if !matches!(player.state, PlayerState::Docking { .. }) {
+                Self::sync_wing(world);
+            }
+        }
     }
 
-    pub fn is_bullet(sprite_id: &str) -> bool {
-        sprite_id.starts_with("bullet_")
+    pub fn has_wing(world: &World) -> bool {
+        world.contains(WING_ID)
     }
 
-    pub fn is_player(sprite_id: &str) -> bool {
-        sprite_id == "player"
+    // Where the second ship sits next to the first. The pair is moved in
+    // from the right edge first if it wouldn't fit.
+    pub fn wing_slot(world: &mut World) -> Option<(f32, f32)> {
+        let (maxw, _) = world.field;
+        let wing_width = world.get(WING_ID).map_or(PLAYER_SIZE.0, |body| body.size.0);
+        let body = world.get_mut("player")?;
+        body.position.0 = body.position.0.min(maxw - body.size.0 - wing_width - 5.0).max(0.0);
+        Some((body.position.0 + body.size.0, body.position.1))
     }
 
-    pub fn player_exists(board: &Gameboard) -> bool {
-        board.2.contains_key("player")
+    fn sync_wing(world: &mut World) {
+        if !Self::has_wing(world) {
+            return;
+        }
+        let Some(slot) = Self::wing_slot(world) else { return };
+        if let Some(wing) = world.get_mut(WING_ID) {
+            wing.position = slot;
+        }
     }
 
-    pub fn update_player_movement(ctx: &mut Context, board: &mut Gameboard) {
-        if board.2.contains_key("player") {
-            unsafe {
-                Self::update_player_state();
-                crate::player::movement::handle_movement_by_state(ctx, board, PLAYER_STATE);
-            }
+    // One of the two ships of a dual fighter was hit. The other carries on
+    // alone, as the main ship if it was the wing that survived.
+    pub fn lose_ship(world: &mut World, ship_id: &str) {
+        let Some(wing) = world.remove(WING_ID) else { return };
+        if ship_id == WING_ID {
+            return;
+        }
+        if let Some(body) = world.get_mut("player") {
+            body.position = wing.position;
         }
     }
-}
\ No newline at end of file
+}
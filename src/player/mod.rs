@@ -1,20 +1,27 @@
 pub mod manager;
 pub mod movement;
 pub mod lives;
+pub mod powerups;
+pub mod capture;
 
-pub use manager::PlayerManager;
-pub use lives::{PlayerLives, LivesDisplayInfo};
-
-use std::time::Instant;
+pub use manager::{PlayerManager, ShotKind, WING_ID};
+pub use capture::PlayerCapture;
+pub use lives::{PlayerLives, PlayerLivesState};
+pub use powerups::{ActivePowerUps, PowerUpKind, PowerUps};
 
+// All times in here are simulation seconds, see `Simulation::time`.
 #[derive(Clone, Copy, Debug)]
 pub enum PlayerState {
-    Idle { last_shot: Option<Instant> },
-    MovingLeft { last_shot: Option<Instant>, speed: f32 },
-    MovingRight { last_shot: Option<Instant>, speed: f32 },
-    MovingBoth { last_shot: Option<Instant>, left_speed: f32, right_speed: f32 },
-    Shooting { direction: MovementDirection, shot_time: Instant },
+    Idle { last_shot: Option<f32> },
+    MovingLeft { last_shot: Option<f32>, speed: f32 },
+    MovingRight { last_shot: Option<f32>, speed: f32 },
+    MovingBoth { last_shot: Option<f32>, left_speed: f32, right_speed: f32 },
+    Shooting { direction: MovementDirection, shot_time: f32 },
     Destroyed,
+    // Being pulled up a tractor beam from `from`. The player has no control.
+    Captured { since: f32, from: (f32, f32) },
+    // A freed ship flying down from `from` to join the player, who waits.
+    Docking { since: f32, from: (f32, f32) },
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -25,7 +32,7 @@ pub enum MovementDirection {
     Both,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct KeysHeld {
     pub left: bool,
     pub right: bool,
@@ -46,15 +53,45 @@ impl KeysHeld {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct ServerMovement {
     pub direction: MovementDirection,
-    pub start_time: Instant,
+    pub start_time: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerControl {
+    pub state: PlayerState,
+    pub keys_held: KeysHeld,
+    pub server_movement: Option<ServerMovement>,
+    pub patrol_direction: MovementDirection,
+    pub charge_start: Option<f32>,
+    pub powerups: ActivePowerUps,
+}
+
+impl PlayerControl {
+    pub const fn new() -> Self {
+        Self {
+            state: PlayerState::Idle { last_shot: None },
+            keys_held: KeysHeld::new(),
+            server_movement: None,
+            patrol_direction: MovementDirection::Right,
+            charge_start: None,
+            powerups: ActivePowerUps::new(),
+        }
+    }
+}
+
+impl Default for PlayerControl {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 // Constants
-pub const STEP: f32 = 1.5;
-pub const BULLET_SPEED: f32 = 8.0;
-pub const MOVEMENT_SPEED: f32 = 300.0;
+pub const MOVEMENT_SPEED: f32 = 90.0;
 pub const SHOOT_COOLDOWN: std::time::Duration = std::time::Duration::from_millis(200);
-pub const SERVER_MOVEMENT_DURATION: std::time::Duration = std::time::Duration::from_millis(100);
\ No newline at end of file
+pub const SERVER_MOVEMENT_DURATION: std::time::Duration = std::time::Duration::from_millis(100);
+pub const CHARGE_BIG_SHOT: std::time::Duration = std::time::Duration::from_millis(500);
+pub const CHARGE_PIERCING_SHOT: std::time::Duration = std::time::Duration::from_millis(1500);
+pub const CHARGE_METER_SEGMENTS: u32 = 5;
\ No newline at end of file
@@ -1,24 +1,25 @@
-use pelican_ui::Context;
-use pelican_game_engine::Gameboard;
+use crate::world::World;
 
-use super::{PlayerState, STEP};
+use super::{PlayerState, WING_ID};
 
-pub fn handle_movement_by_state(ctx: &mut Context, board: &mut Gameboard, player_state: PlayerState) {
-    let (maxw, _) = board.0.size(ctx);
+pub fn handle_movement_by_state(world: &mut World, player_state: PlayerState, dt: f32) {
+    let (maxw, _) = world.field;
+    // With a second ship docked the pair has to fit on the board together.
+    let wing = world.get(WING_ID).map_or(0.0, |body| body.size.0);
 
-    if let Some(sprite) = board.2.get_mut("player") {
-        let current_pos = sprite.position(ctx).0;
+    if let Some(body) = world.get_mut("player") {
+        let current_pos = body.position.0;
 
         match player_state {
-            PlayerState::MovingLeft { speed: _, .. } => {
+            PlayerState::MovingLeft { speed, .. } => {
                 if current_pos > 5.0 {
-                    sprite.adjustments().0 -= STEP;
+                    body.position.0 -= speed * dt;
                 }
             }
 
-            PlayerState::MovingRight { speed: _, .. } => {
-                if current_pos < maxw - sprite.dimensions().0 - 5.0 {
-                    sprite.adjustments().0 += STEP;
+            PlayerState::MovingRight { speed, .. } => {
+                if current_pos < maxw - body.size.0 - wing - 5.0 {
+                    body.position.0 += speed * dt;
                 }
             }
 
@@ -27,9 +28,10 @@ pub fn handle_movement_by_state(ctx: &mut Context, board: &mut Gameboard, player
                 // Currently no-op as in original code
             }
 
-            PlayerState::Idle { .. } | PlayerState::Shooting { .. } | PlayerState::Destroyed => {
+            PlayerState::Idle { .. } | PlayerState::Shooting { .. } | PlayerState::Destroyed
+            | PlayerState::Captured { .. } | PlayerState::Docking { .. } => {
                 // No movement for these states
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::player::{PlayerControl, PlayerLives, PlayerLivesState};
+use crate::world::{Body, EntityKind, World};
+
+const PICKUP_SIZE: (f32, f32) = (28.0, 28.0);
+const PICKUP_FALL_SPEED: f32 = 120.0;
+
+const RAPID_FIRE_DURATION: f32 = 10.0;
+const SPREAD_SHOT_DURATION: f32 = 10.0;
+const SLOW_MOTION_DURATION: f32 = 6.0;
+
+// Rapid fire divides the shot cooldown by this, slow motion runs enemies at
+// this fraction of normal speed.
+pub const RAPID_FIRE_FACTOR: f32 = 2.5;
+pub const SLOW_MOTION_SCALE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerUpKind {
+    RapidFire,
+    SpreadShot,
+    Shield,
+    SlowMotion,
+    ExtraLife,
+}
+
+impl PowerUpKind {
+    // Relative odds of each kind once a drop happens. Extra lives are rare.
+    const WEIGHTS: [(PowerUpKind, u32); 5] = [
+        (PowerUpKind::RapidFire, 30),
+        (PowerUpKind::SpreadShot, 25),
+        (PowerUpKind::Shield, 20),
+        (PowerUpKind::SlowMotion, 20),
+        (PowerUpKind::ExtraLife, 5),
+    ];
+
+    pub fn image(&self) -> &'static str {
+        match self {
+            PowerUpKind::RapidFire => "powerup_rapid",
+            PowerUpKind::SpreadShot => "powerup_spread",
+            PowerUpKind::Shield => "powerup_shield",
+            PowerUpKind::SlowMotion => "powerup_slow",
+            PowerUpKind::ExtraLife => "powerup_life",
+        }
+    }
+}
+
+// Timed effects hold the simulation time they run out at.
+#[derive(Clone, Copy, Debug)]
+pub struct ActivePowerUps {
+    pub rapid_fire_until: f32,
+    pub spread_shot_until: f32,
+    pub slow_motion_until: f32,
+    pub shield: bool,
+}
+
+impl ActivePowerUps {
+    pub const fn new() -> Self {
+        Self { rapid_fire_until: 0.0, spread_shot_until: 0.0, slow_motion_until: 0.0, shield: false }
+    }
+}
+
+pub struct PowerUps;
+
+impl PowerUps {
+    // Called for every kill; `chance` is the drop rate from the settings.
+    pub fn maybe_drop(world: &mut World, rng: &mut StdRng, center: (f32, f32), chance: f32) {
+        if chance <= 0.0 || rng.random_range(0.0..1.0) >= chance {
+            return;
+        }
+
+        let total: u32 = PowerUpKind::WEIGHTS.iter().map(|(_, weight)| weight).sum();
+        let mut roll = rng.random_range(0..total);
+        let kind = PowerUpKind::WEIGHTS.iter()
+            .find(|(_, weight)| {
+                if roll < *weight { return true; }
+                roll -= weight;
+                false
+            })
+            .map(|(kind, _)| *kind)
+            .unwrap_or(PowerUpKind::RapidFire);
+
+        let id = world.next_id("powerup_");
+        let position = (center.0 - PICKUP_SIZE.0 / 2.0, center.1 - PICKUP_SIZE.1 / 2.0);
+        let pickup = Body::new(EntityKind::PowerUp(kind), kind.image(), position, PICKUP_SIZE)
+            .with_velocity((0.0, PICKUP_FALL_SPEED));
+        world.insert(&id, pickup);
+    }
+
+    pub fn apply(kind: PowerUpKind, player: &mut PlayerControl, lives: &mut PlayerLivesState, time: f32) {
+        let active = &mut player.powerups;
+        match kind {
+            PowerUpKind::RapidFire => active.rapid_fire_until = time + RAPID_FIRE_DURATION,
+            PowerUpKind::SpreadShot => active.spread_shot_until = time + SPREAD_SHOT_DURATION,
+            PowerUpKind::Shield => active.shield = true,
+            PowerUpKind::SlowMotion => active.slow_motion_until = time + SLOW_MOTION_DURATION,
+            PowerUpKind::ExtraLife => PlayerLives::add_lives(lives, 1),
+        }
+    }
+
+    pub fn has_rapid_fire(player: &PlayerControl, time: f32) -> bool {
+        time < player.powerups.rapid_fire_until
+    }
+
+    pub fn has_spread_shot(player: &PlayerControl, time: f32) -> bool {
+        time < player.powerups.spread_shot_until
+    }
+
+    pub fn has_shield(player: &PlayerControl) -> bool {
+        player.powerups.shield
+    }
+
+    // How fast enemies run compared to the player right now.
+    pub fn enemy_time_scale(player: &PlayerControl, time: f32) -> f32 {
+        if time < player.powerups.slow_motion_until { SLOW_MOTION_SCALE } else { 1.0 }
+    }
+
+    // Uses up the shield on a hit. Returns whether there was one.
+    pub fn absorb_hit(player: &mut PlayerControl) -> bool {
+        std::mem::take(&mut player.powerups.shield)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timed_power_ups_run_out() {
+        let mut player = PlayerControl::new();
+        let mut lives = PlayerLivesState::default();
+        for kind in [PowerUpKind::RapidFire, PowerUpKind::SpreadShot, PowerUpKind::SlowMotion] {
+            PowerUps::apply(kind, &mut player, &mut lives, 1.0);
+        }
+
+        assert!(PowerUps::has_rapid_fire(&player, 1.0 + RAPID_FIRE_DURATION - 0.1));
+        assert!(!PowerUps::has_rapid_fire(&player, 1.0 + RAPID_FIRE_DURATION));
+        assert!(PowerUps::has_spread_shot(&player, 1.0 + SPREAD_SHOT_DURATION - 0.1));
+        assert!(!PowerUps::has_spread_shot(&player, 1.0 + SPREAD_SHOT_DURATION));
+        assert_eq!(PowerUps::enemy_time_scale(&player, 1.0 + SLOW_MOTION_DURATION - 0.1), SLOW_MOTION_SCALE);
+        assert_eq!(PowerUps::enemy_time_scale(&player, 1.0 + SLOW_MOTION_DURATION), 1.0);
+    }
+
+    #[test]
+    fn the_shield_lasts_until_it_takes_a_hit() {
+        let mut player = PlayerControl::new();
+        let mut lives = PlayerLivesState::default();
+        PowerUps::apply(PowerUpKind::Shield, &mut player, &mut lives, 0.0);
+
+        assert!(PowerUps::has_shield(&player));
+        assert!(PowerUps::absorb_hit(&mut player));
+        assert!(!PowerUps::has_shield(&player));
+        assert!(!PowerUps::absorb_hit(&mut player));
+    }
+
+    #[test]
+    fn an_extra_life_adds_one_life() {
+        let mut player = PlayerControl::new();
+        let mut lives = PlayerLivesState::default();
+        let before = lives.lives;
+        PowerUps::apply(PowerUpKind::ExtraLife, &mut player, &mut lives, 0.0);
+        assert_eq!(lives.lives, before + 1);
+    }
+}
@@ -0,0 +1,324 @@
+use pelican_ui::Context;
+use pelican_ui_std::Offset;
+use pelican_game_engine::{Sprite, Gameboard};
+use std::collections::HashMap;
+
+use crate::player::{PowerUps, CHARGE_METER_SEGMENTS};
+use crate::scoring::POPUP_DIGIT_SIZE;
+use crate::fly::EnemyManager;
+use crate::sim::Simulation;
+use crate::world::EntityKind;
+
+const LIFE_SPRITE_SIZE: (f32, f32) = (20.0, 20.0);
+const LIFE_SPRITE_SPACING: f32 = 35.0;
+const LIFE_SPRITE_START_X: f32 = 20.0;
+const LIFE_SPRITE_Y: f32 = 20.0;
+
+// The multiplier sits under the lives, clear of the scan icons top right.
+const MULTIPLIER_Y: f32 = LIFE_SPRITE_Y + LIFE_SPRITE_SIZE.1 + 10.0;
+
+// The boss HP bar runs across the top of the board.
+const BOSS_BAR_WIDTH: f32 = 240.0;
+const BOSS_BAR_HEIGHT: f32 = 10.0;
+const BOSS_BAR_Y: f32 = 50.0;
+
+// How far the shield bubble reaches past the ship on each side.
+const SHIELD_PADDING: f32 = 8.0;
+const SHIELD_ID: &str = "player_shield";
+
+const CHARGE_SEGMENT_SIZE: (f32, f32) = (8.0, 8.0);
+const CHARGE_SEGMENT_SPACING: f32 = 10.0;
+const CHARGE_METER_GAP: f32 = 6.0;
+
+// Draws a `Simulation` onto the Gameboard. Each body in the world gets a
+// sprite with the same id, except score popups which get one sprite per
+// digit; the HUD sprites (lives, combo multiplier, charge meter, shield, boss
+// HP bar) are drawn from the rest of the simulation state.
+#[derive(Debug, Default)]
+pub struct BoardRenderer {
+    sprites: HashMap<String, (String, (f32, f32))>,
+    popups: HashMap<String, usize>,
+    lives_shown: Option<u32>,
+    multiplier_shown: Option<u32>,
+    boss_hp_shown: Option<u32>,
+}
+
+impl BoardRenderer {
+    pub fn sync(&mut self, sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        self.sync_bodies(sim, ctx, board);
+        self.sync_popups(sim, ctx, board);
+        self.sync_lives(sim.lives.lives, ctx, board);
+        self.sync_multiplier(sim.combo.multiplier(), ctx, board);
+        Self::sync_charge_meter(sim, ctx, board);
+        Self::sync_shield(sim, ctx, board);
+        self.sync_boss_bar(sim, ctx, board);
+    }
+
+    // Forget everything drawn so the next sync starts from an empty board.
+    pub fn clear(&mut self, board: &mut Gameboard) {
+        for id in self.sprites.keys() {
+            Self::remove_sprite(board, id);
+        }
+        self.sprites.clear();
+        for (id, digits) in self.popups.drain() {
+            Self::remove_popup(board, &id, digits);
+        }
+        self.sync_lives_hidden(board);
+        self.sync_multiplier_hidden(board);
+        Self::remove_charge_meter(board);
+        Self::remove_sprite(board, SHIELD_ID);
+        self.remove_boss_bar(board);
+    }
+
+    fn sync_bodies(&mut self, sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        let stale: Vec<String> = self.sprites.keys()
+            .filter(|id| !sim.world.contains(id))
+            .cloned()
+            .collect();
+        for id in stale {
+            Self::remove_sprite(board, &id);
+            self.sprites.remove(&id);
+        }
+
+        for (id, body) in sim.world.bodies.iter() {
+            if matches!(body.kind, EntityKind::ScorePopup(_)) {
+                continue;
+            }
+
+            let look = (body.image.clone(), body.size);
+            let (x, y) = body.position;
+
+            if self.sprites.get(id) == Some(&look) {
+                if let Some(sprite) = board.2.get_mut(id) {
+                    let (sx, sy) = sprite.position(ctx);
+                    sprite.adjustments().0 += x - sx;
+                    sprite.adjustments().1 += y - sy;
+                }
+                continue;
+            }
+
+            Self::remove_sprite(board, id);
+            let sprite = Sprite::new(ctx, id, &body.image, body.size, (Offset::Static(x), Offset::Static(y)));
+            board.insert_sprite(ctx, sprite);
+            self.sprites.insert(id.clone(), look);
+        }
+    }
+
+    fn sync_popups(&mut self, sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        let stale: Vec<(String, usize)> = self.popups.iter()
+            .filter(|(id, _)| !sim.world.contains(id))
+            .map(|(id, digits)| (id.clone(), *digits))
+            .collect();
+        for (id, digits) in stale {
+            Self::remove_popup(board, &id, digits);
+            self.popups.remove(&id);
+        }
+
+        for (id, body) in sim.world.bodies.iter() {
+            let EntityKind::ScorePopup(points) = body.kind else { continue };
+            let (x, y) = body.position;
+
+            for (i, digit) in points.to_string().chars().enumerate() {
+                let digit_id = format!("{}_digit_{}", id, i);
+                let dx = x + i as f32 * POPUP_DIGIT_SIZE.0;
+
+                if let Some(sprite) = board.2.get_mut(&digit_id) {
+                    let (sx, sy) = sprite.position(ctx);
+                    sprite.adjustments().0 += dx - sx;
+                    sprite.adjustments().1 += y - sy;
+                } else {
+                    let image = format!("digit_{}", digit);
+                    let sprite = Sprite::new(ctx, &digit_id, &image, POPUP_DIGIT_SIZE, (Offset::Static(dx), Offset::Static(y)));
+                    board.insert_sprite(ctx, sprite);
+                }
+            }
+            self.popups.insert(id.clone(), points.to_string().len());
+        }
+    }
+
+    fn remove_popup(board: &mut Gameboard, id: &str, digits: usize) {
+        for i in 0..digits {
+            Self::remove_sprite(board, &format!("{}_digit_{}", id, i));
+        }
+    }
+
+    fn sync_lives(&mut self, lives: u32, ctx: &mut Context, board: &mut Gameboard) {
+        if self.lives_shown == Some(lives) {
+            return;
+        }
+
+        self.sync_lives_hidden(board);
+
+        for i in 0..lives {
+            let life_sprite_id = format!("life_{}", i);
+            let x_pos = LIFE_SPRITE_START_X + (i as f32 * LIFE_SPRITE_SPACING);
+
+            let mut life_sprite = Sprite::new(
+                ctx,
+                &life_sprite_id,
+                "spaceship",
+                LIFE_SPRITE_SIZE,
+                (Offset::Static(x_pos), Offset::Static(LIFE_SPRITE_Y))
+            );
+
+            life_sprite.adjustments().0 = 0.0;
+            life_sprite.adjustments().1 = 0.0;
+
+            board.insert_sprite(ctx, life_sprite);
+        }
+
+        self.lives_shown = Some(lives);
+    }
+
+    fn sync_lives_hidden(&mut self, board: &mut Gameboard) {
+        let life_sprite_ids: Vec<String> = board.2.keys()
+            .filter(|id| id.starts_with("life_"))
+            .cloned()
+            .collect();
+
+        for life_id in life_sprite_ids {
+            Self::remove_sprite(board, &life_id);
+        }
+
+        self.lives_shown = None;
+    }
+
+    // "x3" under the lives, only while a streak is worth something.
+    fn sync_multiplier(&mut self, multiplier: u32, ctx: &mut Context, board: &mut Gameboard) {
+        if self.multiplier_shown == Some(multiplier) {
+            return;
+        }
+
+        self.sync_multiplier_hidden(board);
+        if multiplier <= 1 {
+            self.multiplier_shown = Some(multiplier);
+            return;
+        }
+
+        let text = multiplier.to_string();
+        let x = LIFE_SPRITE_START_X;
+
+        let sign = Sprite::new(ctx, "combo_x", "combo_x", POPUP_DIGIT_SIZE, (Offset::Static(x), Offset::Static(MULTIPLIER_Y)));
+        board.insert_sprite(ctx, sign);
+
+        for (i, digit) in text.chars().enumerate() {
+            let dx = x + (i + 1) as f32 * POPUP_DIGIT_SIZE.0;
+            let image = format!("digit_{}", digit);
+            let sprite = Sprite::new(ctx, &format!("combo_digit_{}", i), &image, POPUP_DIGIT_SIZE, (Offset::Static(dx), Offset::Static(MULTIPLIER_Y)));
+            board.insert_sprite(ctx, sprite);
+        }
+
+        self.multiplier_shown = Some(multiplier);
+    }
+
+    fn sync_multiplier_hidden(&mut self, board: &mut Gameboard) {
+        let combo_sprite_ids: Vec<String> = board.2.keys()
+            .filter(|id| id.starts_with("combo_"))
+            .cloned()
+            .collect();
+
+        for combo_id in combo_sprite_ids {
+            Self::remove_sprite(board, &combo_id);
+        }
+
+        self.multiplier_shown = None;
+    }
+
+    fn sync_charge_meter(sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        let meter = sim.charge_fraction().zip(sim.world.get("player"));
+        let Some((fraction, player)) = meter else {
+            Self::remove_charge_meter(board);
+            return;
+        };
+
+        let (px, py) = player.position;
+        let ph = player.size.1;
+        let lit = (fraction * CHARGE_METER_SEGMENTS as f32).floor() as u32;
+        let x = px - CHARGE_SEGMENT_SIZE.0 - CHARGE_METER_GAP;
+
+        for i in 0..CHARGE_METER_SEGMENTS {
+            let id = format!("charge_segment_{}", i);
+            let y = py + ph - (i + 1) as f32 * CHARGE_SEGMENT_SPACING;
+
+            if i >= lit {
+                Self::remove_sprite(board, &id);
+                continue;
+            }
+
+            if let Some(segment) = board.2.get_mut(&id) {
+                let (sx, sy) = segment.position(ctx);
+                segment.adjustments().0 += x - sx;
+                segment.adjustments().1 += y - sy;
+            } else {
+                let image = if i + 2 >= CHARGE_METER_SEGMENTS { "charge_segment_max" } else { "charge_segment" };
+                let segment = Sprite::new(ctx, &id, image, CHARGE_SEGMENT_SIZE, (Offset::Static(x), Offset::Static(y)));
+                board.insert_sprite(ctx, segment);
+            }
+        }
+    }
+
+    fn sync_shield(sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        let player = sim.world.get("player").filter(|_| PowerUps::has_shield(&sim.player));
+        let Some(player) = player else {
+            Self::remove_sprite(board, SHIELD_ID);
+            return;
+        };
+
+        let x = player.position.0 - SHIELD_PADDING;
+        let y = player.position.1 - SHIELD_PADDING;
+
+        if let Some(shield) = board.2.get_mut(SHIELD_ID) {
+            let (sx, sy) = shield.position(ctx);
+            shield.adjustments().0 += x - sx;
+            shield.adjustments().1 += y - sy;
+        } else {
+            let size = (player.size.0 + SHIELD_PADDING * 2.0, player.size.1 + SHIELD_PADDING * 2.0);
+            let shield = Sprite::new(ctx, SHIELD_ID, "shield", size, (Offset::Static(x), Offset::Static(y)));
+            board.insert_sprite(ctx, shield);
+        }
+    }
+
+    // The fill is remade whenever the HP changes since sprites can't resize.
+    fn sync_boss_bar(&mut self, sim: &Simulation, ctx: &mut Context, board: &mut Gameboard) {
+        let Some((hp, max_hp)) = EnemyManager::boss_health(&sim.enemies, &sim.world, sim.enemy_types) else {
+            self.remove_boss_bar(board);
+            return;
+        };
+        if self.boss_hp_shown == Some(hp) {
+            return;
+        }
+
+        let x = (sim.world.field.0 - BOSS_BAR_WIDTH) / 2.0;
+        if self.boss_hp_shown.is_none() {
+            let back = Sprite::new(ctx, "boss_bar_back", "boss_bar_back", (BOSS_BAR_WIDTH, BOSS_BAR_HEIGHT), (Offset::Static(x), Offset::Static(BOSS_BAR_Y)));
+            board.insert_sprite(ctx, back);
+        }
+
+        Self::remove_sprite(board, "boss_bar_fill");
+        let width = BOSS_BAR_WIDTH * hp as f32 / max_hp.max(1) as f32;
+        if width > 0.0 {
+            let fill = Sprite::new(ctx, "boss_bar_fill", "boss_bar_fill", (width, BOSS_BAR_HEIGHT), (Offset::Static(x), Offset::Static(BOSS_BAR_Y)));
+            board.insert_sprite(ctx, fill);
+        }
+
+        self.boss_hp_shown = Some(hp);
+    }
+
+    fn remove_boss_bar(&mut self, board: &mut Gameboard) {
+        Self::remove_sprite(board, "boss_bar_back");
+        Self::remove_sprite(board, "boss_bar_fill");
+        self.boss_hp_shown = None;
+    }
+
+    fn remove_charge_meter(board: &mut Gameboard) {
+        for i in 0..CHARGE_METER_SEGMENTS {
+            Self::remove_sprite(board, &format!("charge_segment_{}", i));
+        }
+    }
+
+    fn remove_sprite(board: &mut Gameboard, id: &str) {
+        if board.2.remove(id).is_some() {
+            board.0.0.remove(id);
+        }
+    }
+}
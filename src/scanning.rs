@@ -0,0 +1,151 @@
+use pelican_ui::Context;
+use pelican_ui_std::Offset;
+use pelican_game_engine::{Sprite, Gameboard};
+use std::time::{Duration, Instant};
+
+use crate::settings::GameSettings;
+use crate::sim::{PlayerAction, SimInput};
+
+const SCAN_ICON_SIZE: (f32, f32) = (36.0, 36.0);
+const SCAN_ICON_SPACING: f32 = 44.0;
+const SCAN_ICON_Y: f32 = 20.0;
+const SCAN_ICON_MARGIN: f32 = 20.0;
+const SCAN_HIGHLIGHT_ID: &str = "scan_highlight";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScanAction {
+    Left,
+    Right,
+    Shoot,
+}
+
+impl ScanAction {
+    const ALL: [ScanAction; 3] = [ScanAction::Left, ScanAction::Right, ScanAction::Shoot];
+
+    fn next(self) -> Self {
+        match self {
+            ScanAction::Left => ScanAction::Right,
+            ScanAction::Right => ScanAction::Shoot,
+            ScanAction::Shoot => ScanAction::Left,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            ScanAction::Left => 0,
+            ScanAction::Right => 1,
+            ScanAction::Shoot => 2,
+        }
+    }
+
+    fn sprite(self) -> (&'static str, &'static str) {
+        match self {
+            ScanAction::Left => ("scan_icon_left", "scan_left"),
+            ScanAction::Right => ("scan_icon_right", "scan_right"),
+            ScanAction::Shoot => ("scan_icon_shoot", "scan_shoot"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ScanState {
+    pub armed: ScanAction,
+    pub last_advance: Option<Instant>,
+}
+
+impl Default for ScanState {
+    fn default() -> Self {
+        Self { armed: ScanAction::Left, last_advance: None }
+    }
+}
+
+pub struct ScanningControl;
+
+impl ScanningControl {
+    pub fn is_enabled(ctx: &mut Context) -> bool {
+        ctx.state().get_mut::<GameSettings>().map(|s| s.scanning_mode).unwrap_or(false)
+    }
+
+    pub fn update(scan: &mut ScanState, ctx: &mut Context, board: &mut Gameboard) {
+        if !Self::is_enabled(ctx) {
+            Self::remove_indicator(board);
+            scan.last_advance = None;
+            return;
+        }
+
+        let interval = ctx.state().get_mut::<GameSettings>()
+            .map(|s| s.scan_interval)
+            .unwrap_or(1.5);
+        let interval = Duration::from_secs_f32(interval.max(0.1));
+
+        match scan.last_advance {
+            None => scan.last_advance = Some(Instant::now()),
+            Some(last) if Instant::now().duration_since(last) >= interval => {
+                scan.armed = scan.armed.next();
+                scan.last_advance = Some(Instant::now());
+            }
+            _ => {}
+        }
+
+        Self::ensure_indicator(ctx, board);
+        Self::update_highlight(scan, board);
+    }
+
+    // The switch press itself carries no direction, it just fires whatever is
+    // highlighted and restarts the scan so the patient has a full interval to
+    // press again for a repeated action.
+    pub fn handle_press(scan: &mut ScanState, input: &mut SimInput) {
+        println!("Scanning press: {:?}", scan.armed);
+
+        input.actions.push(match scan.armed {
+            ScanAction::Left => PlayerAction::MoveLeft,
+            ScanAction::Right => PlayerAction::MoveRight,
+            ScanAction::Shoot => PlayerAction::Shoot,
+        });
+
+        scan.last_advance = Some(Instant::now());
+    }
+
+    fn ensure_indicator(ctx: &mut Context, board: &mut Gameboard) {
+        if board.2.contains_key(SCAN_HIGHLIGHT_ID) {
+            return;
+        }
+
+        let (board_width, _) = board.0.size(ctx);
+        let start_x = board_width - SCAN_ICON_MARGIN - (ScanAction::ALL.len() as f32 * SCAN_ICON_SPACING);
+
+        for action in ScanAction::ALL {
+            let (id, image) = action.sprite();
+            let x = start_x + action.index() as f32 * SCAN_ICON_SPACING;
+            let icon = Sprite::new(ctx, id, image, SCAN_ICON_SIZE, (Offset::Static(x), Offset::Static(SCAN_ICON_Y)));
+            board.insert_sprite(ctx, icon);
+        }
+
+        let highlight = Sprite::new(
+            ctx,
+            SCAN_HIGHLIGHT_ID,
+            "scan_highlight",
+            SCAN_ICON_SIZE,
+            (Offset::Static(start_x), Offset::Static(SCAN_ICON_Y)),
+        );
+        board.insert_sprite(ctx, highlight);
+    }
+
+    fn update_highlight(scan: &ScanState, board: &mut Gameboard) {
+        let offset = scan.armed.index() as f32 * SCAN_ICON_SPACING;
+        if let Some(highlight) = board.2.get_mut(SCAN_HIGHLIGHT_ID) {
+            highlight.adjustments().0 = offset;
+        }
+    }
+
+    pub fn remove_indicator(board: &mut Gameboard) {
+        let mut ids: Vec<&str> = ScanAction::ALL.iter().map(|a| a.sprite().0).collect();
+        ids.push(SCAN_HIGHLIGHT_ID);
+
+        for id in ids {
+            if board.2.remove(id).is_some() {
+                board.0.0.remove(id);
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use crate::fly::fly_state::EnemyBehaviour;
+use crate::fly::fly_types::EnemyType;
+use crate::fly::fly_waves::WaveModifiers;
+use crate::fly::fly_entry::ENTRY_BONUS;
+use crate::world::{Body, EntityKind, World};
+
+// Shooting down an enemy bullet.
+pub const BULLET_SCORE: u32 = 10;
+
+// Clearing a wave straight away is worth the full bonus, falling to nothing
+// at `WAVE_CLEAR_PAR` seconds.
+const WAVE_CLEAR_BONUS: f32 = 1000.0;
+const WAVE_CLEAR_PAR: f32 = 30.0;
+
+// Every enemy of a challenge stage is worth the same, and shooting down all
+// of them earns the perfect bonus on top.
+const CHALLENGE_HIT_SCORE: u32 = 100;
+const PERFECT_BONUS: u32 = 10000;
+
+pub const POPUP_DURATION: Duration = Duration::from_millis(800);
+pub const POPUP_DIGIT_SIZE: (f32, f32) = (14.0, 22.0);
+const POPUP_RISE_SPEED: f32 = 60.0;
+
+pub struct Scoring;
+
+impl Scoring {
+    // Formation kills get the type's base score, divers are worth their dive
+    // score and enemies still flying in are worth double. Challenge stage
+    // enemies are a flat score whatever their type.
+    pub fn enemy_kill(enemy_type: &EnemyType, behaviour: Option<EnemyBehaviour>, modifiers: &WaveModifiers) -> u32 {
+        let base = match behaviour {
+            Some(EnemyBehaviour::Diving | EnemyBehaviour::Returning | EnemyBehaviour::Beaming) => enemy_type.dive_score as f32,
+            Some(EnemyBehaviour::Entering) => enemy_type.score as f32 * ENTRY_BONUS,
+            Some(EnemyBehaviour::Passing) => CHALLENGE_HIT_SCORE as f32,
+            _ => enemy_type.score as f32,
+        };
+        (base * modifiers.score).round() as u32
+    }
+
+    pub fn wave_clear_bonus(elapsed: f32) -> u32 {
+        let left = (1.0 - elapsed / WAVE_CLEAR_PAR).max(0.0);
+        ((WAVE_CLEAR_BONUS * left / 10.0).round() * 10.0) as u32
+    }
+
+    pub fn challenge_bonus(hits: u32, total: u32) -> u32 {
+        if total > 0 && hits == total { PERFECT_BONUS } else { 0 }
+    }
+
+    // A rising number centred on `center`, drawn one digit sprite at a time.
+    pub fn spawn_popup(world: &mut World, center: (f32, f32), points: u32) {
+        if points == 0 {
+            return;
+        }
+
+        let digits = points.to_string().len() as f32;
+        let size = (POPUP_DIGIT_SIZE.0 * digits, POPUP_DIGIT_SIZE.1);
+        let position = (center.0 - size.0 / 2.0, center.1 - size.1 / 2.0);
+
+        let id = world.next_id("score_popup_");
+        let popup = Body::new(EntityKind::ScorePopup(points), "", position, size)
+            .with_velocity((0.0, -POPUP_RISE_SPEED));
+        world.insert(&id, popup);
+    }
+}
+
+// Kills in a row build the multiplier up one step every `COMBO_STEP` kills.
+// Going `COMBO_WINDOW` seconds without a kill, missing a shot or being hit
+// loses the streak.
+const COMBO_STEP: u32 = 5;
+const COMBO_WINDOW: f32 = 3.0;
+pub const MAX_MULTIPLIER: u32 = 5;
+
+#[derive(Debug, Clone, Default)]
+pub struct Combo {
+    pub streak: u32,
+    pub last_kill: f32,
+    // Player bullets that have hit something, so piercing shots that carry
+    // on off the top of the screen are not counted as misses.
+    pub landed: BTreeSet<String>,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.streak / COMBO_STEP).min(MAX_MULTIPLIER)
+    }
+
+    // Counts a kill and returns what `points` is worth with the streak.
+    pub fn kill(&mut self, points: u32, time: f32) -> u32 {
+        self.streak += 1;
+        self.last_kill = time;
+        points * self.multiplier()
+    }
+
+    pub fn mark_landed(&mut self, bullet_id: &str) {
+        self.landed.insert(bullet_id.to_string());
+    }
+
+    // A player bullet has left the top of the screen. It only breaks the
+    // streak if it never hit anything on the way.
+    pub fn bullet_escaped(&mut self, bullet_id: &str) {
+        if !self.landed.remove(bullet_id) {
+            self.reset();
+        }
+    }
+
+    pub fn expire(&mut self, time: f32) {
+        if self.streak > 0 && time - self.last_kill > COMBO_WINDOW {
+            self.reset();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.streak = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fly::fly_types::EnemyTypes;
+
+    fn kill(name: &str, behaviour: Option<EnemyBehaviour>, modifiers: &WaveModifiers) -> u32 {
+        let types = EnemyTypes::bundled();
+        let enemy_type = types.get(types.id_of(name).unwrap()).unwrap();
+        Scoring::enemy_kill(enemy_type, behaviour, modifiers)
+    }
+
+    #[test]
+    fn kills_score_by_type_and_what_the_enemy_was_doing() {
+        let normal = WaveModifiers::default();
+        assert_eq!(kill("b2", None, &normal), 150);
+        assert_eq!(kill("b2", Some(EnemyBehaviour::InFormation), &normal), 150);
+        assert_eq!(kill("b2", Some(EnemyBehaviour::Diving), &normal), 400);
+        assert_eq!(kill("b2", Some(EnemyBehaviour::Returning), &normal), 400);
+        assert_eq!(kill("b2", Some(EnemyBehaviour::Entering), &normal), 300);
+        assert_eq!(kill("tiki_fly", None, &normal), 50);
+        assert_eq!(kill("tiki_fly", Some(EnemyBehaviour::Diving), &normal), 100);
+
+        let doubled = WaveModifiers { score: 2.0, ..WaveModifiers::default() };
+        assert_eq!(kill("northrop", None, &doubled), 160);
+    }
+
+    #[test]
+    fn challenge_enemies_score_the_same_and_only_a_perfect_stage_earns_the_bonus() {
+        let normal = WaveModifiers::default();
+        assert_eq!(kill("northrop", Some(EnemyBehaviour::Passing), &normal), CHALLENGE_HIT_SCORE);
+        assert_eq!(kill("b2", Some(EnemyBehaviour::Passing), &normal), CHALLENGE_HIT_SCORE);
+
+        assert_eq!(Scoring::challenge_bonus(40, 40), PERFECT_BONUS);
+        assert_eq!(Scoring::challenge_bonus(39, 40), 0);
+        assert_eq!(Scoring::challenge_bonus(0, 0), 0);
+    }
+
+    #[test]
+    fn the_wave_clear_bonus_falls_to_nothing_at_par() {
+        assert_eq!(Scoring::wave_clear_bonus(0.0), 1000);
+        assert_eq!(Scoring::wave_clear_bonus(WAVE_CLEAR_PAR / 2.0), 500);
+        assert_eq!(Scoring::wave_clear_bonus(WAVE_CLEAR_PAR), 0);
+        assert_eq!(Scoring::wave_clear_bonus(WAVE_CLEAR_PAR * 2.0), 0);
+    }
+
+    fn streak_of(kills: u32) -> Combo {
+        let mut combo = Combo::default();
+        for _ in 0..kills {
+            combo.kill(10, 0.0);
+        }
+        combo
+    }
+
+    #[test]
+    fn the_multiplier_steps_up_every_few_kills_to_the_cap() {
+        assert_eq!(streak_of(0).multiplier(), 1);
+        assert_eq!(streak_of(COMBO_STEP - 1).multiplier(), 1);
+        assert_eq!(streak_of(COMBO_STEP).multiplier(), 2);
+        assert_eq!(streak_of(COMBO_STEP * 10).multiplier(), MAX_MULTIPLIER);
+        assert_eq!(streak_of(COMBO_STEP).kill(10, 0.0), 20);
+    }
+
+    #[test]
+    fn a_missed_shot_or_a_pause_in_kills_loses_the_streak() {
+        let mut combo = streak_of(3);
+        combo.mark_landed("hit");
+        combo.bullet_escaped("hit");
+        assert_eq!(combo.streak, 3);
+        combo.bullet_escaped("miss");
+        assert_eq!(combo.streak, 0);
+
+        let mut combo = streak_of(3);
+        combo.expire(COMBO_WINDOW);
+        assert_eq!(combo.streak, 3);
+        combo.expire(COMBO_WINDOW + 0.1);
+        assert_eq!(combo.streak, 0);
+    }
+}
@@ -1,19 +1,72 @@
-use std::ptr::addr_of_mut;
+use std::cell::Cell;
 use std::sync::mpsc::{self, Receiver, Sender};
-use tokio::net::{TcpListener, TcpStream};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_rustls::TlsAcceptor;
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 use futures_util::{SinkExt, StreamExt};
 use serde_json::Value;
 use local_ip_address::local_ip;
-use pelican_ui::Context;
 
 use crate::settings::GameSettings;
+use crate::latency::InputLatency;
+use crate::tls::{ServerTransport, TlsIdentity, PLAIN_ENV};
+
+#[derive(Debug, Clone)]
+pub struct InputTiming {
+    pub seq: Option<u64>,
+    pub sent_at: Option<u64>,
+    pub received_at: u64,
+    pub received_instant: Instant,
+    ack: UnboundedSender<String>,
+}
+
+impl InputTiming {
+    fn new(seq: Option<u64>, sent_at: Option<u64>, ack: UnboundedSender<String>) -> Self {
+        Self {
+            seq,
+            sent_at,
+            received_at: now_millis(),
+            received_instant: Instant::now(),
+            ack,
+        }
+    }
+
+    pub fn acknowledge(&self, applied_at: u64, tick: u64) {
+        let Some(seq) = self.seq else { return };
+
+        let message = serde_json::json!({
+            "type": "applied",
+            "seq": seq,
+            "sent_at": self.sent_at,
+            "received_at": self.received_at,
+            "applied_at": applied_at,
+            "tick": tick,
+        });
+        let _ = self.ack.send(message.to_string());
+    }
+}
+
+pub fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
 
 #[derive(Debug, Clone)]
 pub enum ServerEvent {
-    RightPeak(i32),
-    LeftPeak(i32),
-    ShootPeak(i32),
+    RightPeak(i32, InputTiming),
+    LeftPeak(i32, InputTiming),
+    ShootPeak(i32, InputTiming),
+    HoldStart(i32, InputTiming),
+    HoldRelease(InputTiming),
+    Pressure(i32, InputTiming),
+    Start(InputTiming),
+    Pause(InputTiming),
+    Stop(InputTiming),
     ConnectionEstablished,
     ConnectionLost,
 }
@@ -22,31 +75,84 @@ pub enum ServerEvent {
 pub enum GameAction {
     MoveRight,
     MoveLeft,
+    Patrol,
     Shoot,
+    ChargeStart,
+    ChargeRelease,
+    Start,
+    TogglePause,
+    Stop,
+}
+
+impl ServerEvent {
+    pub fn timing(&self) -> Option<&InputTiming> {
+        match self {
+            ServerEvent::RightPeak(_, timing) |
+            ServerEvent::LeftPeak(_, timing) |
+            ServerEvent::ShootPeak(_, timing) |
+            ServerEvent::HoldStart(_, timing) |
+            ServerEvent::HoldRelease(timing) |
+            ServerEvent::Pressure(_, timing) |
+            ServerEvent::Start(timing) |
+            ServerEvent::Pause(timing) |
+            ServerEvent::Stop(timing) => Some(timing),
+            ServerEvent::ConnectionEstablished | ServerEvent::ConnectionLost => None,
+        }
+    }
+
+    fn is_peak(&self) -> bool {
+        matches!(self, ServerEvent::RightPeak(..) | ServerEvent::LeftPeak(..) | ServerEvent::ShootPeak(..))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ServerInfo {
+    pub url: String,
+    pub fingerprint: Option<String>,
 }
 
 pub struct GameServer {
     runtime: tokio::runtime::Runtime,
     event_sender: Sender<ServerEvent>,
     server_handle: Option<tokio::task::JoinHandle<()>>,
+    transport: ServerTransport,
+    tls: Option<TlsIdentity>,
 }
 
 impl GameServer {
     const PORT: u16 = 3030;
 
     pub fn new() -> Result<(Self, Receiver<ServerEvent>), Box<dyn std::error::Error>> {
+        Self::with_transport(ServerTransport::from_env())
+    }
+
+    pub fn with_transport(transport: ServerTransport) -> Result<(Self, Receiver<ServerEvent>), Box<dyn std::error::Error>> {
         let (tx, rx) = mpsc::channel();
         let runtime = tokio::runtime::Runtime::new()?;
 
+        // Plain ws:// is only ever served when asked for, never as a fallback.
+        let tls = TlsIdentity::load(&transport, &local_ip()?.to_string())
+            .map_err(|e| format!("TLS setup failed ({}), set {}=1 to serve plain ws://", e, PLAIN_ENV))?;
+
         let server = GameServer {
             runtime,
             event_sender: tx,
             server_handle: None,
+            transport,
+            tls,
         };
 
         Ok((server, rx))
     }
 
+    pub fn info(&self) -> ServerInfo {
+        let address = Self::get_server_address().unwrap_or_else(|_| format!("0.0.0.0:{}", Self::PORT));
+        ServerInfo {
+            url: format!("{}://{}", self.transport.scheme(), address),
+            fingerprint: self.tls.as_ref().map(|tls| tls.fingerprint.clone()),
+        }
+    }
+
     fn get_server_address() -> Result<String, Box<dyn std::error::Error>> {
         let local_ip = local_ip()?;
         Ok(format!("{}:{}", local_ip, Self::PORT))
@@ -56,11 +162,16 @@ impl GameServer {
         let server_address = Self::get_server_address()?;
         println!("WebSocket Game server starting on {}", server_address);
         println!("Local IP address: {}", local_ip()?);
+
+        if let Some(tls) = &self.tls {
+            println!("Certificate fingerprint (SHA-256): {}", tls.fingerprint);
+        }
         
         let event_sender = self.event_sender.clone();
+        let acceptor = self.tls.as_ref().map(|tls| tls.acceptor.clone());
         
         let handle = self.runtime.spawn(async move {
-            if let Err(e) = Self::run_server(event_sender).await {
+            if let Err(e) = Self::run_server(event_sender, acceptor).await {
                 println!("Server error: {}", e);
             }
         });
@@ -75,10 +186,11 @@ impl GameServer {
         }
     }
 
-    async fn run_server(event_sender: Sender<ServerEvent>) -> Result<(), Box<dyn std::error::Error>> {
+    async fn run_server(event_sender: Sender<ServerEvent>, acceptor: Option<TlsAcceptor>) -> Result<(), Box<dyn std::error::Error>> {
         let server_address = Self::get_server_address()?;
         let listener = TcpListener::bind(&server_address).await?;
-        println!("WebSocket server listening on {}", server_address);
+        let scheme = if acceptor.is_some() { "wss" } else { "ws" };
+        println!("WebSocket server listening on {}://{}", scheme, server_address);
 
         loop {
             match listener.accept().await {
@@ -87,8 +199,17 @@ impl GameServer {
                     let _ = event_sender.send(ServerEvent::ConnectionEstablished);
                     
                     let sender_clone = event_sender.clone();
+                    let acceptor = acceptor.clone();
                     tokio::spawn(async move {
-                        if let Err(e) = Self::handle_client(stream, sender_clone).await {
+                        let result = match acceptor {
+                            Some(acceptor) => match acceptor.accept(stream).await {
+                                Ok(tls_stream) => Self::handle_client(tls_stream, sender_clone).await,
+                                Err(e) => Err(format!("TLS handshake failed: {}", e).into()),
+                            },
+                            None => Self::handle_client(stream, sender_clone).await,
+                        };
+
+                        if let Err(e) = result {
                             println!("Client error: {}", e);
                         }
                     });
@@ -100,10 +221,13 @@ impl GameServer {
         }
     }
 
-    async fn handle_client(
-        stream: TcpStream,
+    async fn handle_client<S>(
+        stream: S,
         event_sender: Sender<ServerEvent>,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
         let ws_stream = accept_async(stream).await?;
         println!("WebSocket connection established");
 
@@ -112,24 +236,53 @@ impl GameServer {
         let welcome_msg = Message::Text("Connected to game server".into());
         ws_sender.send(welcome_msg).await?;
 
-        while let Some(msg) = ws_receiver.next().await {
+        let (ack_sender, mut ack_receiver) = unbounded_channel::<String>();
+
+        loop {
+            let msg = tokio::select! {
+                msg = ws_receiver.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                Some(ack) = ack_receiver.recv() => {
+                    ws_sender.send(Message::Text(ack.into())).await?;
+                    continue;
+                }
+            };
+
             match msg {
                 Ok(Message::Text(text)) => {
                     println!("Received: {}", text);
                     
                     match Self::parse_game_message(&text) {
-                        Ok((action_type, value)) => {
+                        Ok((action_type, value, seq, sent_at)) => {
+                            let timing = InputTiming::new(seq, sent_at, ack_sender.clone());
+                            let received_at = timing.received_at;
                             let event = match action_type.as_str() {
-                                "right" => Some(ServerEvent::RightPeak(value)),
-                                "left" => Some(ServerEvent::LeftPeak(value)),
-                                "shoot" => Some(ServerEvent::ShootPeak(value)),
+                                "right" => Some(ServerEvent::RightPeak(value, timing)),
+                                "left" => Some(ServerEvent::LeftPeak(value, timing)),
+                                "shoot" => Some(ServerEvent::ShootPeak(value, timing)),
+                                "hold_start" => Some(ServerEvent::HoldStart(value, timing)),
+                                "hold_release" => Some(ServerEvent::HoldRelease(timing)),
+                                "pressure" => Some(ServerEvent::Pressure(value, timing)),
+                                "start" => Some(ServerEvent::Start(timing)),
+                                "pause" => Some(ServerEvent::Pause(timing)),
+                                "stop" => Some(ServerEvent::Stop(timing)),
                                 _ => None,
                             };
 
                             match event {
                                 Some(server_event) => {
                                     let _ = event_sender.send(server_event);
-                                    let response = Message::Text("OK".into());
+                                    let response = match seq {
+                                        Some(seq) => Message::Text(serde_json::json!({
+                                            "type": "received",
+                                            "seq": seq,
+                                            "sent_at": sent_at,
+                                            "received_at": received_at,
+                                        }).to_string().into()),
+                                        None => Message::Text("OK".into()),
+                                    };
                                     ws_sender.send(response).await?;
                                 }
                                 None => {
@@ -166,19 +319,23 @@ impl GameServer {
         Ok(())
     }
 
-    fn parse_game_message(message: &str) -> Result<(String, i32), Box<dyn std::error::Error + Send + Sync>> {
+    fn parse_game_message(message: &str) -> Result<(String, i32, Option<u64>, Option<u64>), Box<dyn std::error::Error + Send + Sync>> {
         let json: Value = serde_json::from_str(message)?;
 
         let action = json.get("action")
             .and_then(|a| a.as_str())
             .ok_or("Action field not found")?;
 
-        let value = json.get("value")
-            .and_then(|v| v.as_i64())
-            .map(|v| v as i32)
-            .ok_or("Value field not found or not a number")?;
+        let value = match json.get("value").and_then(|v| v.as_i64()) {
+            Some(v) => v as i32,
+            None if matches!(action, "hold_release" | "start" | "pause" | "stop") => 0,
+            None => return Err("Value field not found or not a number".into()),
+        };
+
+        let seq = json.get("seq").and_then(|s| s.as_u64());
+        let sent_at = json.get("sent_at").and_then(|t| t.as_u64());
 
-        Ok((action.to_string(), value))
+        Ok((action.to_string(), value, seq, sent_at))
     }
 }
 
@@ -190,11 +347,12 @@ impl Drop for GameServer {
 
 pub struct ServerEventHandler {
     receiver: Receiver<ServerEvent>,
+    holding: Cell<bool>,
 }
 
 impl ServerEventHandler {
     pub fn new(receiver: Receiver<ServerEvent>) -> Self {
-        Self { receiver }
+        Self { receiver, holding: Cell::new(false) }
     }
 
     pub fn check_events(&self) -> Vec<ServerEvent> {
@@ -205,40 +363,57 @@ impl ServerEventHandler {
         events
     }
 
-    pub fn process_events_for_game(&self, ctx: &mut Context) -> Option<GameAction> {
+    // Input that arrived while the board was off screen is stale by the time
+    // play resumes, and a release may have been lost with it.
+    pub fn discard_pending(&self) {
+        let dropped = self.check_events().len();
+        self.holding.set(false);
+        if dropped > 0 {
+            println!("Discarded {} controller events received while paused", dropped);
+        }
+    }
+
+    // Every action from the events queued since the last tick, in the order
+    // they arrived.
+    pub fn process_events_for_game(&self, settings: &GameSettings, latency: &mut InputLatency) -> Vec<(GameAction, InputTiming)> {
         let events = self.check_events();
+        let peak_min = settings.get_peak_min();
+        let mut actions = Vec::new();
 
-        let peak_min = if let Some(settings) = ctx.state().get_mut::<GameSettings>() {
-            settings.get_peak_min()
-        } else {
-            500.0 
-        };
+        for timing in events.iter().filter_map(|event| event.timing()) {
+            latency.observe_arrival(timing);
+        }
 
         for event in events {
+            if settings.graded_bands && event.is_peak() {
+                actions.extend(Self::graded_action(settings, event));
+                continue;
+            }
+
             match event {
-                ServerEvent::RightPeak(value) => {
+                ServerEvent::RightPeak(value, timing) => {
                     println!("Right peak: {} (min required: {})", value, peak_min);
                     if value as f32 >= peak_min {
                         println!("Right peak exceeds minimum, sending move right action");
-                        return Some(GameAction::MoveRight);
+                        actions.push((GameAction::MoveRight, timing));
                     } else {
                         println!("Right peak below minimum threshold, ignoring");
                     }
                 }
-                ServerEvent::LeftPeak(value) => {
+                ServerEvent::LeftPeak(value, timing) => {
                     println!("Left peak: {} (min required: {})", value, peak_min);
                     if value as f32 >= peak_min {
                         println!("Left peak exceeds minimum, sending move left action");
-                        return Some(GameAction::MoveLeft);
+                        actions.push((GameAction::MoveLeft, timing));
                     } else {
                         println!("Left peak below minimum threshold, ignoring");
                     }
                 }
-                ServerEvent::ShootPeak(value) => {
+                ServerEvent::ShootPeak(value, timing) => {
                     println!("Shoot peak: {} (min required: {})", value, peak_min);
                     if value as f32 >= peak_min {
                         println!("Shoot peak exceeds minimum, sending shoot action");
-                        return Some(GameAction::Shoot);
+                        actions.push((GameAction::Shoot, timing));
                     } else {
                         println!("Shoot peak below minimum threshold, ignoring");
                     }
@@ -246,12 +421,75 @@ impl ServerEventHandler {
                 ServerEvent::ConnectionEstablished => {
                     println!("Connection established");
                 }
+                ServerEvent::HoldStart(value, timing) => {
+                    println!("Hold start: {} (min required: {})", value, peak_min);
+                    if value as f32 >= peak_min && !self.holding.replace(true) {
+                        actions.push((GameAction::ChargeStart, timing));
+                    }
+                }
+                ServerEvent::HoldRelease(timing) => {
+                    println!("Hold release");
+                    if self.holding.replace(false) {
+                        actions.push((GameAction::ChargeRelease, timing));
+                    }
+                }
+                ServerEvent::Pressure(value, timing) => {
+                    let above = value as f32 >= peak_min;
+                    if above && !self.holding.replace(true) {
+                        println!("Pressure {} crossed minimum, starting charge", value);
+                        actions.push((GameAction::ChargeStart, timing));
+                    } else if !above && self.holding.replace(false) {
+                        println!("Pressure {} dropped below minimum, releasing charge", value);
+                        actions.push((GameAction::ChargeRelease, timing));
+                    }
+                }
+                ServerEvent::Start(timing) => {
+                    println!("Start requested by controller");
+                    actions.push((GameAction::Start, timing));
+                }
+                ServerEvent::Pause(timing) => {
+                    println!("Pause requested by controller");
+                    actions.push((GameAction::TogglePause, timing));
+                }
+                ServerEvent::Stop(timing) => {
+                    println!("Stop requested by controller");
+                    actions.push((GameAction::Stop, timing));
+                }
                 ServerEvent::ConnectionLost => {
                     println!("Connection lost");
+                    if self.holding.replace(false) {
+                        println!("Releasing held charge after disconnect");
+                    }
                 }
             }
         }
 
-        None
+        actions
+    }
+
+    // With graded bands the strength of a peak picks the action: a light press
+    // moves toward the channel it arrived on (or patrols back and forth when a
+    // single sensor reports on the shoot channel), a hard press shoots.
+    fn graded_action(settings: &GameSettings, event: ServerEvent) -> Option<(GameAction, InputTiming)> {
+        let (value, move_action, timing) = match event {
+            ServerEvent::RightPeak(value, timing) => (value, GameAction::MoveRight, timing),
+            ServerEvent::LeftPeak(value, timing) => (value, GameAction::MoveLeft, timing),
+            ServerEvent::ShootPeak(value, timing) => (value, GameAction::Patrol, timing),
+            _ => return None,
+        };
+
+        let value = value as f32;
+        println!("Graded peak: {} (move band: {}-{}, shoot above {})", value, settings.band_move_min, settings.band_shoot_min, settings.band_shoot_min);
+
+        if value >= settings.band_shoot_min {
+            println!("Peak in shoot band, sending shoot action");
+            Some((GameAction::Shoot, timing))
+        } else if value >= settings.band_move_min {
+            println!("Peak in move band, sending {:?} action", move_action);
+            Some((move_action, timing))
+        } else {
+            println!("Peak below move band, ignoring");
+            None
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+use pelican_ui::Context;
+use std::time::{Duration, Instant};
+
+use crate::latency::InputLatency;
+use crate::player::PlayerManager;
+use crate::scanning::ScanState;
+use crate::server::{GameServer, ServerEventHandler};
+use crate::phase::{GamePhase, PauseItem};
+use crate::overlay::PhaseOverlay;
+use crate::clock::SimClock;
+use crate::sim::{Simulation, SimInput};
+use crate::render::BoardRenderer;
+use crate::fly::fly_waves::WaveSet;
+
+// Everything that used to live in static muts across game.rs and the
+// player/enemy managers. One session per board. The game itself is `sim`;
+// the rest is the wall-clock and device state around it.
+#[derive(Default)]
+pub struct GameSession {
+    pub tick_count: u64,
+    pub phase: GamePhase,
+    pub pause_item: PauseItem,
+    pub paused_at: Option<Instant>,
+    pub play_time: Duration,
+    pub playing_since: Option<Instant>,
+    pub clock: SimClock,
+
+    pub sim: Simulation,
+    pub input: SimInput,
+    pub renderer: BoardRenderer,
+    pub overlay: PhaseOverlay,
+    pub scanning: ScanState,
+    pub latency: InputLatency,
+    pub wave_sets: Vec<WaveSet>,
+
+    pub server: Option<GameServer>,
+    pub event_handler: Option<ServerEventHandler>,
+}
+
+impl GameSession {
+    pub fn new() -> Self {
+        Self {
+            wave_sets: WaveSet::load_all(),
+            ..Self::default()
+        }
+    }
+
+    // Puts the session back to the start of a game. The server connection,
+    // latency history and loaded wave sets are kept, they belong to the
+    // device not the run.
+    pub fn reset(&mut self) {
+        let server = self.server.take();
+        let event_handler = self.event_handler.take();
+        let latency = std::mem::take(&mut self.latency);
+        let wave_sets = std::mem::take(&mut self.wave_sets);
+
+        *self = Self::default();
+
+        self.server = server;
+        self.event_handler = event_handler;
+        self.latency = latency;
+        self.wave_sets = wave_sets;
+    }
+
+    // A run starts from a clean simulation on the given seed, or a random one,
+    // playing the chosen wave set with endless waves capped at `max_targets`.
+    // The seed stays with the simulation so it can be shown on the results.
+    pub fn start_run(&mut self, seed: Option<u64>, wave_set: Option<&str>, max_targets: u32, boss_every: u32, challenge_every: u32) {
+        self.sim = Simulation::with_seed(seed.unwrap_or_else(rand::random));
+        if let Some(waves) = self.wave_set(wave_set) {
+            self.sim.waves = waves.clone();
+        }
+        self.sim.enemies.max_targets = Some(max_targets as usize);
+        self.sim.enemies.boss_every = boss_every;
+        self.sim.enemies.challenge_every = challenge_every;
+        self.input = SimInput::default();
+    }
+
+    // The named set, or the first one loaded if it is missing or unset.
+    pub fn wave_set(&self, name: Option<&str>) -> Option<&WaveSet> {
+        name.and_then(|name| self.wave_sets.iter().find(|set| set.name == name))
+            .or_else(|| self.wave_sets.first())
+    }
+
+    // Called whenever play stops, whether for the pause screen or Settings.
+    // Nothing held at that moment can be released, so drop it rather than
+    // leave the ship running.
+    pub fn pause(&mut self) {
+        if self.paused_at.is_some() {
+            return;
+        }
+
+        if let Some(since) = self.playing_since.take() {
+            self.play_time += Instant::now().duration_since(since);
+        }
+
+        self.paused_at = Some(Instant::now());
+        self.clock.stop();
+        self.input = SimInput::default();
+        self.sim.player.server_movement = None;
+        PlayerManager::cancel_charge(&mut self.sim.player);
+    }
+
+    // Simulation time only moves while stepping, so only the wall-clock
+    // timers outside it need pushing forward by the time spent away.
+    pub fn resume(&mut self) {
+        let Some(paused_at) = self.paused_at.take() else { return };
+        let away = Instant::now().duration_since(paused_at);
+
+        if let Some(event_handler) = &self.event_handler {
+            event_handler.discard_pending();
+        }
+
+        self.shift_timers(away);
+        self.playing_since = Some(Instant::now());
+    }
+
+    fn shift_timers(&mut self, by: Duration) {
+        self.phase.shift(by);
+        self.scanning.last_advance.iter_mut().for_each(|time| *time += by);
+    }
+
+    // The session lives in ctx.state() between events. It is moved out for
+    // the duration of `f` so managers can borrow it and the context together.
+    pub fn with<R>(ctx: &mut Context, f: impl FnOnce(&mut GameSession, &mut Context) -> R) -> Option<R> {
+        let mut session = std::mem::take(ctx.state().get_mut::<GameSession>()?);
+        let result = f(&mut session, ctx);
+        if let Some(slot) = ctx.state().get_mut::<GameSession>() {
+            *slot = session;
+        }
+        Some(result)
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Serialize, Deserialize};
 
+const ENDLESS_TARGETS: [u32; 4] = [6, 10, 15, 20];
+const POWER_UP_DROP_CHANCES: [f32; 4] = [0.0, 0.05, 0.1, 0.2];
+const BOSS_INTERVALS: [u32; 4] = [0, 3, 5, 8];
+const CHALLENGE_INTERVALS: [u32; 4] = [0, 3, 4, 6];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameSettings {
     pub can_fly: bool,
@@ -14,6 +19,22 @@ pub struct GameSettings {
     pub bullet_speed_player: f32,
 
     pub peak_min: f32,
+
+    pub scanning_mode: bool,
+    pub scan_interval: f32,
+
+    pub graded_bands: bool,
+    pub band_move_min: f32,
+    pub band_shoot_min: f32,
+
+    pub seed: Option<u64>,
+    pub wave_set: Option<String>,
+    // Most enemies at once in an endless wave.
+    pub endless_targets: u32,
+
+    pub power_up_drop_chance: f32,
+    pub boss_every: u32,
+    pub challenge_every: u32,
 }
 
 impl GameSettings {
@@ -24,9 +45,20 @@ impl GameSettings {
             player_auto_shoot: false,
             player_auto_move: false,
             player_invincible: false,
-            bullet_speed_fly: 800.0,
-            bullet_speed_player: 600.0,
+            bullet_speed_fly: 360.0,
+            bullet_speed_player: 480.0,
             peak_min: 500.0,
+            scanning_mode: false,
+            scan_interval: 1.5,
+            graded_bands: false,
+            band_move_min: 500.0,
+            band_shoot_min: 800.0,
+            seed: None,
+            wave_set: None,
+            endless_targets: 20,
+            power_up_drop_chance: 0.1,
+            boss_every: 5,
+            challenge_every: 3,
         }
     }
 
@@ -50,6 +82,14 @@ impl GameSettings {
         self.player_invincible = !self.player_invincible;
     }
 
+    pub fn toggle_scanning_mode(&mut self) {
+        self.scanning_mode = !self.scanning_mode;
+    }
+
+    pub fn toggle_graded_bands(&mut self) {
+        self.graded_bands = !self.graded_bands;
+    }
+
     pub fn set_bullet_speed_fly(&mut self, speed: f32) {
         self.bullet_speed_fly = speed;
     }
@@ -65,6 +105,88 @@ impl GameSettings {
     pub fn get_peak_min(&self) -> f32 {
         self.peak_min
     }
+
+    pub fn set_scan_interval(&mut self, seconds: f32) {
+        self.scan_interval = seconds.clamp(0.5, 5.0);
+    }
+
+    pub fn set_band_move_min(&mut self, peak: f32) {
+        self.band_move_min = peak.clamp(0.0, self.band_shoot_min - 50.0);
+    }
+
+    pub fn set_band_shoot_min(&mut self, peak: f32) {
+        self.band_shoot_min = peak.clamp(self.band_move_min + 50.0, 1000.0);
+    }
+
+    // None picks a fresh seed for every run.
+    pub fn set_seed(&mut self, seed: Option<u64>) {
+        self.seed = seed;
+    }
+
+    pub fn set_wave_set(&mut self, name: &str) {
+        self.wave_set = Some(name.to_string());
+    }
+
+    pub fn cycle_endless_targets(&mut self) {
+        let index = ENDLESS_TARGETS.iter()
+            .position(|targets| *targets >= self.endless_targets)
+            .unwrap_or(0);
+        self.endless_targets = ENDLESS_TARGETS[(index + 1) % ENDLESS_TARGETS.len()];
+    }
+
+    pub fn endless_label(&self) -> String {
+        format!("Endless Targets: Up To {}", self.endless_targets)
+    }
+
+    // Steps through the offered drop rates, wrapping back to none.
+    pub fn cycle_power_up_drop_chance(&mut self) {
+        let index = POWER_UP_DROP_CHANCES.iter()
+            .position(|chance| *chance >= self.power_up_drop_chance)
+            .unwrap_or(0);
+        self.power_up_drop_chance = POWER_UP_DROP_CHANCES[(index + 1) % POWER_UP_DROP_CHANCES.len()];
+    }
+
+    pub fn power_up_label(&self) -> String {
+        match self.power_up_drop_chance {
+            chance if chance <= 0.0 => "Power-Up Drops: Off".to_string(),
+            chance => format!("Power-Up Drops: {:.0}%", chance * 100.0),
+        }
+    }
+
+    pub fn cycle_boss_every(&mut self) {
+        let index = BOSS_INTERVALS.iter()
+            .position(|every| *every >= self.boss_every)
+            .unwrap_or(0);
+        self.boss_every = BOSS_INTERVALS[(index + 1) % BOSS_INTERVALS.len()];
+    }
+
+    pub fn boss_label(&self) -> String {
+        match self.boss_every {
+            0 => "Boss Waves: Off".to_string(),
+            every => format!("Boss Waves: Every {}", every),
+        }
+    }
+
+    pub fn cycle_challenge_every(&mut self) {
+        let index = CHALLENGE_INTERVALS.iter()
+            .position(|every| *every >= self.challenge_every)
+            .unwrap_or(0);
+        self.challenge_every = CHALLENGE_INTERVALS[(index + 1) % CHALLENGE_INTERVALS.len()];
+    }
+
+    pub fn challenge_label(&self) -> String {
+        match self.challenge_every {
+            0 => "Challenge Stages: Off".to_string(),
+            every => format!("Challenge Stages: Every {}", every),
+        }
+    }
+
+    pub fn seed_label(&self) -> String {
+        match self.seed {
+            Some(seed) => format!("Run Seed: {}", seed),
+            None => "Run Seed: Random".to_string(),
+        }
+    }
 }
 
 impl Default for GameSettings {
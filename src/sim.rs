@@ -0,0 +1,588 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+use crate::collision::CollisionManager;
+use crate::fly::{EnemyGlobalState, EnemyManager};
+use crate::fly::fly_beam::CAPTURED_SHIP_ID;
+use crate::fly::fly_types::EnemyTypes;
+use crate::fly::fly_waves::WaveSet;
+use crate::scoring::{Combo, Scoring};
+use crate::player::{PlayerCapture, PlayerControl, PlayerLives, PlayerLivesState, PlayerManager, PowerUps};
+use crate::settings::GameSettings;
+use crate::world::{EntityKind, World};
+
+const BULLET_MARGIN: f32 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayerAction {
+    MoveLeft,
+    MoveRight,
+    Patrol,
+    Shoot,
+    ChargeStart,
+    ChargeRelease,
+}
+
+// What the player is doing for one step: the held directions plus any
+// one-off actions since the last step.
+#[derive(Debug, Clone, Default)]
+pub struct SimInput {
+    pub left: bool,
+    pub right: bool,
+    pub actions: Vec<PlayerAction>,
+}
+
+impl SimInput {
+    // Held directions carry over to the next step, actions are used once.
+    pub fn take(&mut self) -> SimInput {
+        SimInput {
+            left: self.left,
+            right: self.right,
+            actions: std::mem::take(&mut self.actions),
+        }
+    }
+}
+
+// The game rules with no UI attached. It is stepped with an input and a time
+// delta and keeps everything in `world`, which the Gameboard only draws.
+// All gameplay randomness comes from `rng`, so a run is replayed exactly by
+// starting from the same seed and feeding the same inputs.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub seed: u64,
+    pub rng: StdRng,
+    pub time: f32,
+    // Runs behind `time` while enemies are slowed down by a power-up. All
+    // enemy timers use this one.
+    pub enemy_time: f32,
+    pub world: World,
+    // Enemies each piercing shot has already hit, so one passing through an
+    // enemy only damages it once.
+    pub pierced: BTreeMap<String, BTreeSet<String>>,
+    pub score: u32,
+    pub combo: Combo,
+    pub enemies_destroyed: u32,
+    pub enemies_created: bool,
+    pub player: PlayerControl,
+    pub lives: PlayerLivesState,
+    pub enemies: EnemyGlobalState,
+    pub enemy_types: &'static EnemyTypes,
+    pub waves: WaveSet,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulation {
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            time: 0.0,
+            enemy_time: 0.0,
+            world: World::default(),
+            pierced: BTreeMap::new(),
+            score: 0,
+            combo: Combo::default(),
+            enemies_destroyed: 0,
+            enemies_created: false,
+            player: PlayerControl::new(),
+            lives: PlayerLivesState::default(),
+            enemies: EnemyGlobalState::default(),
+            enemy_types: EnemyTypes::shared(),
+            waves: WaveSet::default(),
+        }
+    }
+
+    // The field is whatever size the renderer has to draw into. Until it is
+    // known there is nowhere to put the player, so they arrive with it.
+    pub fn set_field(&mut self, field: (f32, f32)) {
+        let first = self.world.field == (0.0, 0.0);
+        self.world.field = field;
+        if first && field != (0.0, 0.0) {
+            PlayerManager::create_player(&mut self.world);
+        }
+    }
+
+    pub fn ensure_enemies(&mut self) {
+        if !self.enemies_created {
+            EnemyManager::create_enemies(&mut self.enemies, &mut self.world, self.enemy_types, &self.waves, &mut self.rng, self.enemy_time);
+            self.enemies_created = true;
+        }
+    }
+
+    pub fn is_player_dead(&self) -> bool {
+        PlayerManager::is_player_destroyed(&self.player)
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        PlayerLives::is_game_over(&self.lives, &self.player)
+    }
+
+    pub fn charge_fraction(&self) -> Option<f32> {
+        PlayerManager::charge_fraction(&self.player, self.time)
+    }
+
+    pub fn remove_body(&mut self, id: &str) {
+        let Some(removed) = self.world.remove(id) else { return };
+        if removed.kind.is_enemy() {
+            EnemyManager::remove_enemy_from_base_positions(&mut self.enemies, id);
+        }
+        if removed.kind.is_player_bullet() {
+            self.combo.landed.remove(id);
+            self.pierced.remove(id);
+        }
+    }
+
+    pub fn step(&mut self, input: &SimInput, settings: &GameSettings, dt: f32) {
+        self.time += dt;
+        self.world.time = self.time;
+        let time = self.time;
+
+        let enemy_scale = PowerUps::enemy_time_scale(&self.player, time);
+        let enemy_dt = dt * enemy_scale;
+        self.enemy_time += enemy_dt;
+        let enemy_time = self.enemy_time;
+
+        self.ensure_enemies();
+
+        if !self.is_player_dead() {
+            self.apply_input(input, settings);
+        }
+
+        PlayerLives::update(&mut self.lives, &mut self.player, &mut self.world, time);
+
+        if !self.is_player_dead() {
+            PlayerManager::update_player_movement(&mut self.player, &mut self.world, time, dt);
+        }
+        self.update_capture(time);
+
+        EnemyManager::update_behaviours(&mut self.enemies, &mut self.world, self.enemy_types, enemy_time);
+        EnemyManager::update_challenge(&mut self.enemies, &mut self.world, self.enemy_types, enemy_time);
+        EnemyManager::update_entries(&self.enemies, &mut self.world, self.enemy_types, enemy_time);
+        EnemyManager::update_dives(&mut self.enemies, &mut self.world, self.enemy_types, &mut self.rng, enemy_time, settings.bullet_speed_fly, settings.can_fly);
+        let beam = settings.can_fly && PlayerCapture::is_single(&self.player, &self.world);
+        EnemyManager::update_beams(&mut self.enemies, &mut self.world, self.enemy_types, &mut self.rng, enemy_time, beam);
+        EnemyManager::update_enemy_pulse(&mut self.enemies, &mut self.world, enemy_dt);
+        EnemyManager::update_enemy_shooting(&mut self.enemies, &mut self.world, self.enemy_types, &mut self.rng, enemy_time, settings.bullet_speed_fly);
+        EnemyManager::update_boss(&mut self.enemies, &mut self.world, self.enemy_types, enemy_time, settings.bullet_speed_fly);
+
+        self.world.advance_scaled(dt, |kind| if *kind == EntityKind::EnemyBullet { enemy_scale } else { 1.0 });
+        for id in self.world.out_of_field(|kind| kind.is_bullet() || kind.is_power_up(), BULLET_MARGIN) {
+            if self.world.get(&id).is_some_and(|body| body.kind.is_player_bullet() && body.position.1 < 0.0) {
+                self.combo.bullet_escaped(&id);
+            }
+            self.remove_body(&id);
+        }
+        self.combo.expire(time);
+
+        if !self.is_player_dead() && !settings.player_invincible && !PlayerCapture::is_captured(&self.player) {
+            if let Some((ship_id, player_hit_pos)) = CollisionManager::handle_player_enemy_bullet_collisions(self) {
+                if PowerUps::absorb_hit(&mut self.player) {
+                    CollisionManager::spawn_hit_spark(self, player_hit_pos);
+                } else if PlayerManager::has_wing(&self.world) {
+                    // A dual fighter loses the ship that was hit, not a life.
+                    PlayerManager::lose_ship(&mut self.world, &ship_id);
+                    CollisionManager::spawn_explosion(self, player_hit_pos);
+                    self.combo.reset();
+                } else {
+                    self.remove_body("player");
+                    CollisionManager::spawn_explosion(self, player_hit_pos);
+                    PlayerLives::handle_player_death(&mut self.lives, &mut self.player, time);
+                    self.combo.reset();
+                }
+            }
+
+            let exposed = !PlayerLives::is_invulnerable(&self.lives, time) && PlayerCapture::is_single(&self.player, &self.world);
+            let captor = if exposed { CollisionManager::handle_player_beam_collisions(self) } else { None };
+            if let Some(captor) = captor {
+                EnemyManager::start_capture(&mut self.enemies, &captor, enemy_time);
+                PlayerCapture::capture(&mut self.player, &self.world, time);
+            }
+        }
+
+        self.score += CollisionManager::handle_bullet_bullet_collisions(self);
+
+        let (kills, points) = CollisionManager::handle_player_bullet_enemy_collisions(self);
+        if !kills.is_empty() {
+            self.enemies_destroyed += kills.len() as u32;
+            self.score += points;
+        }
+        for center in kills {
+            PowerUps::maybe_drop(&mut self.world, &mut self.rng, center, settings.power_up_drop_chance);
+        }
+        self.dock_freed_ship(time);
+
+        if !self.is_player_dead() {
+            for kind in CollisionManager::handle_player_power_up_collisions(self) {
+                PowerUps::apply(kind, &mut self.player, &mut self.lives, time);
+            }
+        }
+
+        CollisionManager::update_explosions(self);
+
+        let cleared = EnemyManager::check_and_manage_enemy_state(&mut self.enemies, &mut self.world, self.enemy_types, &self.waves, &mut self.rng, enemy_time);
+        if let Some(wave) = cleared {
+            let bonus = match wave.challenge {
+                Some((hits, total)) => Scoring::challenge_bonus(hits, total),
+                None => Scoring::wave_clear_bonus(wave.elapsed),
+            };
+            self.score += bonus;
+            let (w, h) = self.world.field;
+            Scoring::spawn_popup(&mut self.world, (w / 2.0, h / 2.0), bonus);
+        }
+    }
+
+    // Pulls a captured player up the beam. Once they reach the captor the
+    // ship is taken, which costs a life, and the captor carries it off.
+    fn update_capture(&mut self, time: f32) {
+        let captor = EnemyManager::captor_position(&self.enemies, &self.world);
+        if !PlayerCapture::update(&mut self.player, &mut self.world, captor, time) {
+            return;
+        }
+
+        let size = self.world.get("player").map(|body| body.size).unwrap_or_default();
+        self.remove_body("player");
+        EnemyManager::hold_ship(&mut self.enemies, &mut self.world, size);
+        PlayerLives::handle_player_death(&mut self.lives, &mut self.player, time);
+        self.combo.reset();
+    }
+
+    // A captor holding the player's ship was shot down. The ship joins the
+    // player as a dual fighter, or is lost if there is no lone ship to join.
+    fn dock_freed_ship(&mut self, time: f32) {
+        if !EnemyManager::take_freed_ship(&mut self.enemies) {
+            return;
+        }
+        let Some(ship) = self.world.remove(CAPTURED_SHIP_ID) else { return };
+
+        if PlayerCapture::is_single(&self.player, &self.world) {
+            PlayerCapture::dock(&mut self.player, &mut self.world, ship.position, time);
+        }
+    }
+
+    // Lets explosions finish while play is stopped without moving anything else.
+    pub fn step_effects(&mut self, dt: f32) {
+        self.time += dt;
+        self.world.time = self.time;
+        CollisionManager::update_explosions(self);
+    }
+
+    fn apply_input(&mut self, input: &SimInput, settings: &GameSettings) {
+        let time = self.time;
+        let bullet_speed = settings.bullet_speed_player;
+        PlayerManager::set_keys_held(&mut self.player, input.left, input.right, time);
+
+        for action in &input.actions {
+            match action {
+                PlayerAction::MoveLeft => PlayerManager::handle_server_move_left(&mut self.player, time),
+                PlayerAction::MoveRight => PlayerManager::handle_server_move_right(&mut self.player, time),
+                PlayerAction::Patrol => PlayerManager::handle_server_patrol(&mut self.player, &mut self.world, time),
+                PlayerAction::Shoot => PlayerManager::handle_server_shoot(&mut self.player, &mut self.world, time, bullet_speed),
+                PlayerAction::ChargeStart => PlayerManager::start_charge(&mut self.player, time),
+                PlayerAction::ChargeRelease => PlayerManager::release_charge(&mut self.player, &mut self.world, time, bullet_speed),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SIM_STEP;
+    use crate::player::ShotKind;
+    use crate::player::lives::STARTING_LIVES;
+    use crate::player::WING_ID;
+    use crate::fly::fly_behaviour::EnemyBehaviours;
+    use crate::fly::fly_state::{Dive, EnemyBehaviour};
+    use crate::world::Body;
+
+    const FIELD: (f32, f32) = (800.0, 600.0);
+
+    fn sim() -> Simulation {
+        let mut sim = Simulation::with_seed(7);
+        sim.set_field(FIELD);
+        sim
+    }
+
+    fn run(sim: &mut Simulation, input: &SimInput, steps: u32) {
+        run_with(sim, input, &GameSettings::new(), steps);
+    }
+
+    fn run_with(sim: &mut Simulation, input: &SimInput, settings: &GameSettings, steps: u32) {
+        for _ in 0..steps {
+            sim.step(input, settings, SIM_STEP);
+        }
+    }
+
+    // Nothing shoots or dives, so the only hits are the ones a test makes.
+    fn calm() -> GameSettings {
+        GameSettings { can_fly: false, can_shoot: false, ..GameSettings::new() }
+    }
+
+    fn hit_player(sim: &mut Simulation) {
+        let player = sim.world.get("player").unwrap();
+        let (position, size) = (player.position, player.size);
+        sim.world.insert("test_bullet", Body::new(EntityKind::EnemyBullet, "bullet_downward", position, size));
+        run_with(sim, &SimInput::default(), &calm(), 1);
+    }
+
+    fn player_x(sim: &Simulation) -> f32 {
+        sim.world.get("player").unwrap().position.0
+    }
+
+    #[test]
+    fn holding_right_moves_the_player_right() {
+        let mut sim = sim();
+        let start = player_x(&sim);
+        run(&mut sim, &SimInput { right: true, ..SimInput::default() }, 30);
+        assert!(player_x(&sim) > start);
+    }
+
+    #[test]
+    fn holding_left_moves_the_player_left() {
+        let mut sim = sim();
+        let start = player_x(&sim);
+        run(&mut sim, &SimInput { left: true, ..SimInput::default() }, 30);
+        assert!(player_x(&sim) < start);
+    }
+
+    #[test]
+    fn shooting_fires_a_bullet_that_flies_up() {
+        let mut sim = sim();
+        run(&mut sim, &SimInput { actions: vec![PlayerAction::Shoot], ..SimInput::default() }, 1);
+        let bullets = sim.world.ids_of(EntityKind::is_player_bullet);
+        assert_eq!(bullets.len(), 1);
+
+        let fired_at = sim.world.get(&bullets[0]).unwrap().position.1;
+        run(&mut sim, &SimInput::default(), 10);
+        assert!(sim.world.get(&bullets[0]).unwrap().position.1 < fired_at);
+    }
+
+    #[test]
+    fn the_first_wave_arrives() {
+        let mut sim = sim();
+        run(&mut sim, &SimInput::default(), 1);
+        assert!(sim.enemies_created);
+
+        run(&mut sim, &SimInput::default(), 600);
+        assert!(sim.world.count_of(EntityKind::is_enemy) > 0);
+        assert_eq!(sim.score, 0);
+        assert!(!sim.is_game_over());
+    }
+
+    #[test]
+    fn a_piercing_shot_damages_each_enemy_once() {
+        let mut sim = sim();
+        let b2 = sim.enemy_types.id_of("b2").unwrap();
+        sim.world.insert("b2_1", Body::new(EntityKind::Enemy(b2), "b2", (100.0, 100.0), (50.0, 50.0)).with_hp(3));
+        sim.world.insert("shot", Body::new(EntityKind::PlayerBullet(ShotKind::Piercing), "bullet", (120.0, 120.0), (10.0, 10.0)));
+
+        for _ in 0..5 {
+            CollisionManager::handle_player_bullet_enemy_collisions(&mut sim);
+        }
+        assert_eq!(sim.world.get("b2_1").unwrap().hp, 2);
+        assert!(sim.world.contains("shot"));
+
+        sim.remove_body("shot");
+        assert!(sim.pierced.is_empty());
+    }
+
+    #[test]
+    fn each_death_costs_a_life_until_the_game_is_over() {
+        let mut sim = sim();
+        run_with(&mut sim, &SimInput::default(), &calm(), 1);
+
+        for lives_left in (0..STARTING_LIVES).rev() {
+            hit_player(&mut sim);
+            assert!(sim.is_player_dead());
+            assert_eq!(sim.lives.lives, lives_left);
+            if lives_left == 0 {
+                break;
+            }
+
+            assert!(!sim.is_game_over());
+            run_with(&mut sim, &SimInput::default(), &calm(), 150);
+            assert!(!sim.is_player_dead());
+            assert!(sim.world.contains("player"));
+        }
+
+        assert!(sim.is_game_over());
+    }
+
+    #[test]
+    fn being_hit_loses_the_combo() {
+        let mut sim = sim();
+        run_with(&mut sim, &SimInput::default(), &calm(), 1);
+        sim.combo.kill(10, sim.time);
+        sim.combo.kill(10, sim.time);
+
+        hit_player(&mut sim);
+        assert_eq!(sim.combo.streak, 0);
+    }
+
+    #[test]
+    fn clearing_a_wave_scores_a_bonus_and_sends_the_next() {
+        let mut sim = sim();
+        run_with(&mut sim, &SimInput::default(), &calm(), 600);
+        assert!(sim.enemies.pending.is_empty());
+        let wave = sim.enemies.wave_count;
+
+        for id in sim.world.ids_of(EntityKind::is_enemy) {
+            sim.remove_body(&id);
+        }
+        run_with(&mut sim, &SimInput::default(), &calm(), 1);
+
+        assert_eq!(sim.enemies.wave_count, wave + 1);
+        assert!(sim.score > 0);
+        assert!(sim.world.count_of(EntityKind::is_enemy) > 0 || !sim.enemies.pending.is_empty());
+    }
+
+    // Plays a challenge stage as the first wave, shooting down every enemy
+    // that flies in except the first if `spare_one` is set. Returns the bonus
+    // the stage scored when it ended.
+    fn play_challenge(spare_one: bool) -> (Simulation, u32) {
+        let mut sim = sim();
+        sim.enemies.challenge_every = 1;
+        let mut spared: Option<String> = None;
+
+        for _ in 0..3000 {
+            for id in sim.world.ids_of(EntityKind::is_enemy) {
+                if spare_one && spared.get_or_insert_with(|| id.clone()) == &id {
+                    continue;
+                }
+                let body = sim.world.get(&id).unwrap();
+                if body.hp > 0 {
+                    let shot = Body::new(EntityKind::PlayerBullet(ShotKind::Normal), "bullet", body.position, body.size);
+                    let shot_id = sim.world.next_id("test_shot_");
+                    sim.world.insert(&shot_id, shot);
+                }
+            }
+
+            let before = sim.score;
+            run_with(&mut sim, &SimInput::default(), &calm(), 1);
+            if sim.enemies.wave_count > 0 {
+                return (sim.clone(), sim.score - before);
+            }
+        }
+        panic!("the challenge stage never ended");
+    }
+
+    #[test]
+    fn shooting_down_a_whole_challenge_stage_earns_the_perfect_bonus() {
+        let (sim, bonus) = play_challenge(false);
+        let (hits, total) = (sim.enemies.challenge_hits, sim.enemies.challenge_total);
+        assert!(total > 0);
+        assert_eq!(hits, total);
+        assert_eq!(bonus, Scoring::challenge_bonus(total, total));
+        assert!(bonus > 0);
+    }
+
+    #[test]
+    fn a_challenge_enemy_that_gets_away_costs_the_bonus() {
+        let (sim, bonus) = play_challenge(true);
+        let (hits, total) = (sim.enemies.challenge_hits, sim.enemies.challenge_total);
+        assert_eq!(hits, total - 1);
+        assert_eq!(bonus, 0);
+    }
+
+    // Parks a b2 above the player on a beam dive with its beam on.
+    fn beam_down(sim: &mut Simulation) {
+        let b2 = sim.enemy_types.id_of("b2").unwrap();
+        let player = sim.world.get("player").unwrap().position;
+        let slot = (player.0, 100.0);
+        sim.world.insert("captor", Body::new(EntityKind::Enemy(b2), "b2", slot, (50.0, 50.0)).with_hp(2));
+        sim.enemies.base_positions.insert("captor".to_string(), slot);
+        EnemyBehaviours::set(&mut sim.enemies, "captor", EnemyBehaviour::Beaming, sim.enemy_time);
+        sim.enemies.dives.insert("captor".to_string(), Dive { target: player, side: -1.0, shot: true, beam: true });
+    }
+
+    fn captured_sim() -> Simulation {
+        let mut sim = sim();
+        run_with(&mut sim, &SimInput::default(), &calm(), 1);
+        beam_down(&mut sim);
+
+        run_with(&mut sim, &SimInput::default(), &calm(), 2);
+        assert!(PlayerCapture::is_captured(&sim.player));
+        run_with(&mut sim, &SimInput::default(), &calm(), 100);
+        sim
+    }
+
+    #[test]
+    fn the_beam_pulls_the_player_up_and_takes_the_ship() {
+        let sim = captured_sim();
+        assert_eq!(sim.lives.lives, STARTING_LIVES - 1);
+        assert!(sim.is_player_dead());
+        assert!(sim.world.contains(CAPTURED_SHIP_ID));
+    }
+
+    #[test]
+    fn shooting_the_captor_frees_the_ship_to_dock_and_a_hit_then_costs_only_the_wing() {
+        let mut sim = captured_sim();
+        run_with(&mut sim, &SimInput::default(), &calm(), 150);
+        assert!(!sim.is_player_dead());
+
+        while sim.world.get("captor").is_some_and(|captor| captor.hp > 0) {
+            let captor = sim.world.get("captor").unwrap();
+            let shot = Body::new(EntityKind::PlayerBullet(ShotKind::Normal), "bullet", captor.position, captor.size);
+            sim.world.insert("test_shot", shot);
+            run_with(&mut sim, &SimInput::default(), &calm(), 1);
+        }
+        assert!(sim.world.contains(WING_ID));
+        assert!(!sim.world.contains(CAPTURED_SHIP_ID));
+
+        run_with(&mut sim, &SimInput::default(), &calm(), 80);
+        assert!(PlayerManager::has_wing(&sim.world));
+
+        let lives = sim.lives.lives;
+        hit_player(&mut sim);
+        assert_eq!(sim.lives.lives, lives);
+        assert!(!PlayerManager::has_wing(&sim.world));
+        assert!(!sim.is_player_dead());
+    }
+
+    #[test]
+    fn a_captor_that_gets_away_takes_the_ship_with_it() {
+        let mut sim = captured_sim();
+        sim.remove_body("captor");
+        run_with(&mut sim, &SimInput::default(), &calm(), 1);
+        assert!(!sim.world.contains(CAPTURED_SHIP_ID));
+    }
+
+    // Steers left and right in turns and fires now and then, with dives on so
+    // the seeded RNG picks divers, targets and shots.
+    fn replay(seed: u64) -> Simulation {
+        let mut sim = Simulation::with_seed(seed);
+        sim.set_field(FIELD);
+        let settings = GameSettings { can_fly: true, ..GameSettings::new() };
+        for step in 0..3600u32 {
+            let input = SimInput {
+                left: step / 90 % 2 == 0,
+                right: step / 90 % 2 == 1,
+                actions: if step % 20 == 0 { vec![PlayerAction::Shoot] } else { Vec::new() },
+            };
+            sim.step(&input, &settings, SIM_STEP);
+        }
+        sim
+    }
+
+    #[test]
+    fn same_seed_and_inputs_replay_the_same_run() {
+        let a = replay(42);
+        let b = replay(42);
+        assert!(a.enemies_destroyed > 0);
+        assert_eq!(a.world.bodies, b.world.bodies);
+        assert_eq!(a.score, b.score);
+        assert_eq!(a.lives.lives, b.lives.lives);
+        assert_eq!(a.enemies_destroyed, b.enemies_destroyed);
+    }
+}
@@ -0,0 +1,124 @@
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sha2::{Digest, Sha256};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+use crate::user_data::UserData;
+
+const TLS_DIR: &str = "tls";
+const CERT_FILE: &str = "server_cert.pem";
+const KEY_FILE: &str = "server_key.pem";
+// The host the self-signed certificate was issued for.
+const HOST_FILE: &str = "server_host.txt";
+
+pub const PLAIN_ENV: &str = "RAMP_GAMES_PLAIN_WS";
+const CERT_ENV: &str = "RAMP_GAMES_TLS_CERT";
+const KEY_ENV: &str = "RAMP_GAMES_TLS_KEY";
+
+#[derive(Debug, Clone)]
+pub enum ServerTransport {
+    Plain,
+    SelfSigned { dir: PathBuf },
+    Configured { cert_path: PathBuf, key_path: PathBuf },
+}
+
+impl ServerTransport {
+    // RAMP_GAMES_PLAIN_WS=1 keeps the old ws:// listener for development,
+    // RAMP_GAMES_TLS_CERT/KEY point at a hospital-issued pair, otherwise we
+    // fall back to a self-signed certificate kept in the user data directory.
+    pub fn from_env() -> Self {
+        if std::env::var(PLAIN_ENV).map(|v| v == "1" || v == "true").unwrap_or(false) {
+            return ServerTransport::Plain;
+        }
+
+        match (std::env::var(CERT_ENV), std::env::var(KEY_ENV)) {
+            (Ok(cert_path), Ok(key_path)) => ServerTransport::Configured {
+                cert_path: PathBuf::from(cert_path),
+                key_path: PathBuf::from(key_path),
+            },
+            _ => ServerTransport::SelfSigned { dir: UserData::path(TLS_DIR) },
+        }
+    }
+
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            ServerTransport::Plain => "ws",
+            _ => "wss",
+        }
+    }
+}
+
+pub struct TlsIdentity {
+    pub acceptor: TlsAcceptor,
+    pub fingerprint: String,
+}
+
+impl TlsIdentity {
+    pub fn load(transport: &ServerTransport, host: &str) -> Result<Option<Self>, Box<dyn std::error::Error>> {
+        match transport {
+            ServerTransport::Plain => Ok(None),
+            ServerTransport::SelfSigned { dir } => {
+                let (cert_path, key_path) = Self::ensure_self_signed(dir, host)?;
+                Ok(Some(Self::from_pem_files(&cert_path, &key_path)?))
+            }
+            ServerTransport::Configured { cert_path, key_path } => {
+                Ok(Some(Self::from_pem_files(cert_path, key_path)?))
+            }
+        }
+    }
+
+    fn ensure_self_signed(dir: &Path, host: &str) -> Result<(PathBuf, PathBuf), Box<dyn std::error::Error>> {
+        let cert_path = dir.join(CERT_FILE);
+        let key_path = dir.join(KEY_FILE);
+        let host_path = dir.join(HOST_FILE);
+
+        // The certificate names the host, so a new address needs a new one.
+        let same_host = fs::read_to_string(&host_path).is_ok_and(|issued| issued.trim() == host);
+        if cert_path.exists() && key_path.exists() && same_host {
+            return Ok((cert_path, key_path));
+        }
+
+        fs::create_dir_all(dir)?;
+
+        let names = vec![host.to_string(), "localhost".to_string()];
+        let certified = rcgen::generate_simple_self_signed(names)?;
+
+        fs::write(&cert_path, certified.cert.pem())?;
+        fs::write(&key_path, certified.key_pair.serialize_pem())?;
+        fs::write(&host_path, host)?;
+
+        println!("Generated self-signed certificate at {}", cert_path.display());
+        Ok((cert_path, key_path))
+    }
+
+    fn from_pem_files(cert_path: &Path, key_path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(File::open(cert_path)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = rustls_pemfile::private_key(&mut BufReader::new(File::open(key_path)?))?
+            .ok_or("No private key found in key file")?;
+
+        let leaf = certs.first().ok_or("No certificate found in cert file")?;
+        let fingerprint = Self::fingerprint(leaf.as_ref());
+
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)?;
+
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(config)),
+            fingerprint,
+        })
+    }
+
+    fn fingerprint(der: &[u8]) -> String {
+        Sha256::digest(der)
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+const APP_DIR: &str = "ramp_games";
+const DATA_DIR_ENV: &str = "RAMP_GAMES_DATA_DIR";
+
+// The one directory everything the user or the game keeps between runs lives
+// in: the TLS identity, enemy types and wave sets. It has to be somewhere
+// writable inside an app bundle or sandbox, unlike the working directory.
+// RAMP_GAMES_DATA_DIR moves it, for development and tests.
+pub struct UserData;
+
+impl UserData {
+    pub fn dir() -> PathBuf {
+        std::env::var(DATA_DIR_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| dirs::data_dir().unwrap_or_else(std::env::temp_dir).join(APP_DIR))
+    }
+
+    pub fn path(name: &str) -> PathBuf {
+        Self::dir().join(name)
+    }
+}
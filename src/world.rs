@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use crate::player::{PowerUpKind, ShotKind};
+use crate::fly::fly_types::EnemyTypeId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Player,
+    Enemy(EnemyTypeId),
+    PlayerBullet(ShotKind),
+    EnemyBullet,
+    Explosion,
+    HitSpark,
+    // Points floating up from where they were scored.
+    ScorePopup(u32),
+    PowerUp(PowerUpKind),
+    // The beam an enemy holds over the player to capture them.
+    TractorBeam,
+    // The player's ship while an enemy carries it off.
+    CapturedShip,
+}
+
+impl EntityKind {
+    pub fn is_enemy(&self) -> bool {
+        matches!(self, EntityKind::Enemy(_))
+    }
+
+    pub fn is_player_bullet(&self) -> bool {
+        matches!(self, EntityKind::PlayerBullet(_))
+    }
+
+    pub fn is_bullet(&self) -> bool {
+        matches!(self, EntityKind::PlayerBullet(_) | EntityKind::EnemyBullet)
+    }
+
+    pub fn is_power_up(&self) -> bool {
+        matches!(self, EntityKind::PowerUp(_))
+    }
+}
+
+// Everything the simulation knows about a thing on screen. The renderer turns
+// each body into a Gameboard sprite with the same id; game code only ever
+// looks at `kind`, never at the id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    pub kind: EntityKind,
+    pub owner: Option<String>,
+    pub hp: u32,
+    pub velocity: (f32, f32),
+    pub spawned_at: f32,
+    pub image: String,
+    pub position: (f32, f32),
+    pub size: (f32, f32),
+}
+
+impl Body {
+    pub fn new(kind: EntityKind, image: &str, position: (f32, f32), size: (f32, f32)) -> Self {
+        Self {
+            kind,
+            owner: None,
+            hp: 1,
+            velocity: (0.0, 0.0),
+            spawned_at: 0.0,
+            image: image.to_string(),
+            position,
+            size,
+        }
+    }
+
+    pub fn with_owner(mut self, owner: &str) -> Self {
+        self.owner = Some(owner.to_string());
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: (f32, f32)) -> Self {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_hp(mut self, hp: u32) -> Self {
+        self.hp = hp;
+        self
+    }
+}
+
+// The entity registry. Bodies are kept in a BTreeMap so every pass over them
+// happens in the same order, which keeps a run reproducible from its inputs.
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    pub field: (f32, f32),
+    pub time: f32,
+    pub bodies: BTreeMap<String, Body>,
+    next_id: u64,
+}
+
+impl World {
+    pub fn next_id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    // Stamps the body with the current simulation time as it goes in.
+    pub fn insert(&mut self, id: &str, mut body: Body) {
+        body.spawned_at = self.time;
+        self.bodies.insert(id.to_string(), body);
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<Body> {
+        self.bodies.remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Body> {
+        self.bodies.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Body> {
+        self.bodies.get_mut(id)
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.bodies.contains_key(id)
+    }
+
+    pub fn of_kind(&self, filter: impl Fn(&EntityKind) -> bool) -> impl Iterator<Item = (&String, &Body)> {
+        self.bodies.iter().filter(move |(_, body)| filter(&body.kind))
+    }
+
+    pub fn ids_of(&self, filter: impl Fn(&EntityKind) -> bool) -> Vec<String> {
+        self.of_kind(filter).map(|(id, _)| id.clone()).collect()
+    }
+
+    pub fn count_of(&self, filter: impl Fn(&EntityKind) -> bool) -> usize {
+        self.of_kind(filter).count()
+    }
+
+    // Moves every body along its velocity, `scale` slowing down or speeding up
+    // some kinds. Things that steer themselves, like the player and the
+    // formation, keep a zero velocity and are left alone.
+    pub fn advance_scaled(&mut self, dt: f32, scale: impl Fn(&EntityKind) -> f32) {
+        for body in self.bodies.values_mut() {
+            let dt = dt * scale(&body.kind);
+            body.position.0 += body.velocity.0 * dt;
+            body.position.1 += body.velocity.1 * dt;
+        }
+    }
+
+    // Ids of matching bodies that have left the field by more than `margin`.
+    pub fn out_of_field(&self, filter: impl Fn(&EntityKind) -> bool, margin: f32) -> Vec<String> {
+        let (width, height) = self.field;
+        self.of_kind(filter)
+            .filter(|(_, body)| {
+                let (x, y) = body.position;
+                y < -margin || y > height + margin || x < -margin || x > width + margin
+            })
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}